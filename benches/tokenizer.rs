@@ -0,0 +1,58 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use retort_js::tokenizer::tokenizer_mod::{tokenizer, Tokenizer, TokenizerState};
+
+/// Builds a presenter with `rows` rows, each with props, a component, a comment and some raw text,
+/// which is roughly what a large page looks like.
+fn get_markup(rows: usize) -> String {
+    let mut markup = String::from("<div class=\"table\">\n");
+    for i in 0..rows {
+        markup += &format!(
+            "  <div id=\"row-{i}\" class={{state.rows[{i}].class}} hidden>\n    \
+             <!-- row {i} -->\n    \
+             <Row title=\"Row {i}\" on:click={{() => select({i})}} />\n    \
+             <p>Row {i} costs {{state.rows[{i}].price}} — été</p>\n    \
+             <input type=\"checkbox\" checked disabled/>\n    \
+             <style>.row-{i} {{ color: red; }}</style>\n  \
+             </div>\n"
+        );
+    }
+    markup += "</div>";
+    markup
+}
+
+fn count_with_closure(markup: &str) -> usize {
+    let mut generator = tokenizer(markup.to_owned());
+    let mut count = 0usize;
+    loop {
+        let current_state = generator().unwrap();
+        if matches!(current_state.state, TokenizerState::Finalized) {
+            return count;
+        }
+        count += 1;
+    }
+}
+
+fn count_with_iterator(markup: &str) -> usize {
+    Tokenizer::new(markup)
+        .map(|next_result| next_result.unwrap())
+        .count()
+}
+
+fn bench_tokenizers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tokenizer");
+    for rows in [10usize, 100, 1000] {
+        let markup = get_markup(rows);
+        assert_eq!(count_with_closure(&markup), count_with_iterator(&markup));
+        group.throughput(Throughput::Bytes(markup.len() as u64));
+        group.bench_with_input(BenchmarkId::new("closure", rows), &markup, |b, markup| {
+            b.iter(|| count_with_closure(black_box(markup)))
+        });
+        group.bench_with_input(BenchmarkId::new("iterator", rows), &markup, |b, markup| {
+            b.iter(|| count_with_iterator(black_box(markup)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_tokenizers);
+criterion_main!(benches);
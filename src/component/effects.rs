@@ -1,8 +1,17 @@
 pub mod effects_mod {
-    use wasm_bindgen::JsValue;
-    use web_sys::js_sys::{Array, Function};
+    use std::sync::atomic::{AtomicU32, Ordering};
 
-    use crate::{component::component_mod::Component, error::error_mod::Error};
+    use wasm_bindgen::{JsCast, JsValue};
+    use web_sys::js_sys::{Array, Function, Object, Reflect, JSON};
+
+    use crate::{
+        component::component_mod::Component,
+        const_util::const_util_mod::{
+            DEFAULT_MAX_UPDATE_DEPTH, EFFECT_CALLBACK_KEY, EFFECT_DEPS_FN_KEY,
+            STATE_TRANSITIONS_LIMIT,
+        },
+        error::error_mod::Error,
+    };
 
     /// An enum to keep variants of different effects in a component.
     pub enum Effects {
@@ -11,10 +20,122 @@ pub mod effects_mod {
         ComponentWillUnmount,
     }
 
+    /// How many times in a row `ComponentDidUpdate` effects may update the state of a component before
+    /// they are considered to be stuck in an infinite loop.
+    static MAX_UPDATE_DEPTH: AtomicU32 = AtomicU32::new(DEFAULT_MAX_UPDATE_DEPTH);
+
+    /// Changes the maximum number of consecutive state updates caused by `ComponentDidUpdate` effects.
+    pub fn set_max_update_depth(depth: u32) {
+        MAX_UPDATE_DEPTH.store(depth, Ordering::Relaxed);
+    }
+
+    // Effects which are registered with `Component::register_effect` are stored as plain functions.
+    // Effects with a dependency list are stored as record objects instead, which keep the callback and
+    // the function that returns its dependencies. Records are shared by every copy of a component, so
+    // what their last run left behind is kept apart, in an `EffectRun` of each mounted instance.
+
+    /// The dependencies of the last run of an effect record, as a JSON string, and the cleanup
+    /// function returned from it. Each mounted instance keeps one of these per effect, at the index of
+    /// the effect in `Component::get_effects`, since the props of instances may differ.
+    #[derive(Debug, Clone, Default)]
+    pub struct EffectRun {
+        deps: Option<String>,
+        cleanup: Option<Function>,
+    }
+
+    /// Creates the record of an effect which only runs when the values returned by `deps_fn` change.
+    pub fn create_effect_record(callback: Function, deps_fn: Function) -> Object {
+        let record = Object::new();
+        let _ = Reflect::set(&record, &EFFECT_CALLBACK_KEY.into(), &callback);
+        let _ = Reflect::set(&record, &EFFECT_DEPS_FN_KEY.into(), &deps_fn);
+        record
+    }
+
+    /// Reads the field named `key` of an effect record; `undefined` is returned if it is missing.
+    fn get_record_field(record: &JsValue, key: &str) -> JsValue {
+        Reflect::get(record, &key.into()).unwrap_or(JsValue::undefined())
+    }
+
+    /// Turns a `JsValue` thrown from an effect into an `Error`.
+    fn get_effect_error(error: JsValue) -> Error {
+        let msg = format!("{:?}", error);
+        Error::EvaluationError(msg)
+    }
+
+    /// Calls the cleanup function which the last run of an effect record returned, if any, and forgets
+    /// it afterwards.
+    fn run_effect_cleanup(run: &mut EffectRun) -> Result<(), Error> {
+        let cleanup = run.cleanup.take();
+        if cleanup.is_none() {
+            return Ok(());
+        }
+        let cleanup_result = cleanup.unwrap().call0(&JsValue::undefined());
+        if cleanup_result.is_err() {
+            return Err(get_effect_error(cleanup_result.unwrap_err()));
+        }
+        Ok(())
+    }
+
+    /// Runs an effect record if the values returned by its `deps_fn` differ from the ones of its last
+    /// run, which `run` holds. Dependencies are compared by value, using their JSON representation, since
+    /// state and props are parsed again on every update. The cleanup of the previous run is called
+    /// first; if the effect returns a function, it is kept as the next cleanup. Returns the new state,
+    /// or `undefined` if the effect was skipped or returned no state.
+    fn run_effect_record(
+        record: &JsValue,
+        run: &mut EffectRun,
+        args: &Array,
+        state: &JsValue,
+        props: &JsValue,
+    ) -> Result<JsValue, Error> {
+        let deps_fn: Function = get_record_field(record, EFFECT_DEPS_FN_KEY).unchecked_into();
+        let deps_result = deps_fn.call2(&JsValue::undefined(), state, props);
+        if deps_result.is_err() {
+            return Err(get_effect_error(deps_result.unwrap_err()));
+        }
+        let deps = deps_result.unwrap();
+        if !Array::is_array(&deps) {
+            return Err(Error::TypeError(format!(
+                "Expected the dependency function of an effect to return an array, but it returned: {:?}",
+                deps
+            )));
+        }
+        let deps_string = JSON::stringify(&deps)
+            .ok()
+            .and_then(|deps_string| deps_string.as_string());
+        if deps_string.is_none() {
+            return Err(Error::TypeError(
+                "Dependencies of an effect must be valid JSON values.".to_owned(),
+            ));
+        }
+        let deps_string = deps_string.unwrap();
+        if run.deps.as_ref() == Some(&deps_string) {
+            return Ok(JsValue::undefined());
+        }
+        run.deps = Some(deps_string);
+
+        let cleanup_result = run_effect_cleanup(run);
+        if cleanup_result.is_err() {
+            return Err(cleanup_result.unwrap_err());
+        }
+        let callback: Function = get_record_field(record, EFFECT_CALLBACK_KEY).unchecked_into();
+        let effect_result = callback.apply(&JsValue::undefined(), args);
+        if effect_result.is_err() {
+            return Err(get_effect_error(effect_result.unwrap_err()));
+        }
+        let returned_value = effect_result.unwrap();
+        if returned_value.is_function() {
+            run.cleanup = Some(returned_value.unchecked_into());
+            return Ok(JsValue::undefined());
+        }
+        Ok(returned_value)
+    }
+
     /// Implementation details for running initial effects of a component, traditionally known
     /// as `component_did_mount`. Returns `Ok` if no error occurs while running effects; an `Err` variant
     /// explaining why otherwise.
-    /// NOTE that its logic is partially incomplete. after any state update, a repaint must be done.
+    /// Effects only update the state of `component`; if they do, `mount_component` patches the DOM
+    /// right after they have run.
     fn component_did_mount_runner(
         component: &mut Component,
         prev_state: &JsValue,
@@ -54,62 +175,145 @@ pub mod effects_mod {
     }
 
     /// Implementation details for running effects of a component, traditionally known as
-    /// `component_did_update`.
-    /// NOTE that its logic is partially incomplete. after any state update, a repaint must be done.
+    /// `component_did_update`. Effects run again as long as any of them updates the state; `depth` is
+    /// the number of times this has happened so far and `transitions` holds the last few states they
+    /// led to. Once `depth` reaches the maximum update depth, an `Error::MaxUpdateDepthExceeded` is
+    /// returned instead of running the effects again.
+    /// Effects only update the state of `component`; the caller patches the DOM once they are done,
+    /// like `set_state` does. `effect_runs` belong to the mounted instance the effects run for.
     fn component_did_update_runner(
         component: &mut Component,
+        effect_runs: &mut Vec<EffectRun>,
         prev_state: &JsValue,
         prev_props: &JsValue,
         state: Option<&JsValue>, // `state` and `props` are used as a workaround for calling this variant during the initial render.
         props: Option<&JsValue>, // no caller from outside of this module can provide `Some` variant for these parameters, because the
-                                 // exposed public function passes `None` by default. this way we make sure that effects run with the
-                                 // initial state -and not the possibly updated version created by `component_did_mount` effects-
-                                 // during the first render.
+        // exposed public function passes `None` by default. this way we make sure that effects run with the
+        // initial state -and not the possibly updated version created by `component_did_mount` effects-
+        // during the first render.
+        depth: u32,
+        transitions: &mut Vec<String>,
     ) -> Result<(), Error> {
         let effects = component.get_effects().clone();
-        let effect_callbacks = effects.into_iter().map(|f| Into::<Function>::into(f));
-        let mut state_was_updated = false;
-        for effect in effect_callbacks {
+        let mut updating_effect: Option<usize> = None;
+        for (index, effect) in effects.into_iter().enumerate() {
+            let current_state = state
+                .map(|state| state.to_owned())
+                .unwrap_or(component.state_parsed());
             let args: Array = Array::of4(
                 prev_props,
                 &JsValue::undefined(), // TODO: this must be replaced with correct value.
                 prev_state,
-                state.unwrap_or(&component.state_parsed()),
+                &current_state,
             );
-            let effect_result = effect.apply(&JsValue::undefined(), &args);
+            let effect_result = if effect.is_function() {
+                let effect: Function = effect.unchecked_into();
+                effect
+                    .apply(&JsValue::undefined(), &args)
+                    .map_err(get_effect_error)
+            } else {
+                let current_props = props
+                    .map(|props| props.to_owned())
+                    .unwrap_or(component.props_parsed());
+                if effect_runs.len() <= index {
+                    effect_runs.resize(index + 1, EffectRun::default());
+                }
+                run_effect_record(
+                    &effect,
+                    &mut effect_runs[index],
+                    &args,
+                    &current_state,
+                    &current_props,
+                )
+            };
             if effect_result.is_err() {
-                let error = effect_result.unwrap_err();
-                let msg = format!("{:?}", error);
-                return Err(Error::EvaluationError(msg));
+                return Err(effect_result.unwrap_err());
             } else {
                 let new_state = effect_result.unwrap();
                 if !new_state.is_undefined() {
-                    state_was_updated = true;
+                    updating_effect = updating_effect.or(Some(index));
                     let set_state_result = component.set_state_with_value(new_state);
                     if set_state_result.is_err() {
                         return Err(set_state_result.unwrap_err());
                     }
+                    transitions.push(component.get_state().to_owned());
+                    if transitions.len() > STATE_TRANSITIONS_LIMIT {
+                        transitions.remove(0);
+                    }
                 }
             }
         }
 
-        if state_was_updated {
-            return component_did_update_runner(component, prev_state, prev_props, state, props);
+        if updating_effect.is_some() {
+            if depth >= MAX_UPDATE_DEPTH.load(Ordering::Relaxed) {
+                return Err(Error::MaxUpdateDepthExceeded(
+                    updating_effect.unwrap(),
+                    transitions.to_owned(),
+                ));
+            }
+            return component_did_update_runner(
+                component,
+                effect_runs,
+                prev_state,
+                prev_props,
+                state,
+                props,
+                depth + 1,
+                transitions,
+            );
         }
 
         Ok(())
     }
 
-    /// not implemented
-    fn component_will_unmount_runner() -> Result<(), Error> {
+    /// Implementation details for running the cleanup effects of a component, traditionally known as
+    /// `component_will_unmount`. They run right before the DOM nodes of the component are removed,
+    /// after the ones of its nested components. Values returned from these effects are ignored, since
+    /// the component won't be rendered again.
+    /// Cleanup functions which effects with a dependency list returned for this instance, kept in
+    /// `effect_runs`, run before these effects.
+    /// Returns `Ok` if no error occurs while running effects; an `Err` variant explaining why otherwise.
+    fn component_will_unmount_runner(
+        component: &mut Component,
+        effect_runs: &mut Vec<EffectRun>,
+        prev_state: &JsValue,
+        prev_props: &JsValue,
+    ) -> Result<(), Error> {
+        for run in effect_runs.iter_mut() {
+            let cleanup_result = run_effect_cleanup(run);
+            if cleanup_result.is_err() {
+                return Err(cleanup_result.unwrap_err());
+            }
+        }
+
+        let effects = component.get_component_will_unmount().clone();
+        let effect_callbacks = effects.into_iter().map(|f| Into::<Function>::into(f));
+
+        for effect in effect_callbacks {
+            let args: Array = Array::of4(
+                prev_props,
+                &JsValue::undefined(), // TODO: this must be replaced with correct value.
+                prev_state,
+                &component.state_parsed(),
+            );
+
+            let effect_result = effect.apply(&JsValue::undefined(), &args);
+            if effect_result.is_err() {
+                let error = effect_result.unwrap_err();
+                let msg = format!("{:?}", error);
+                return Err(Error::EvaluationError(msg));
+            }
+        }
         return Ok(());
     }
 
     /// Exposes effect runners to other modules. This function must be the only way of accessing functionality
-    /// in this module to the outer modules.
+    /// in this module to the outer modules. `effect_runs` are what effects with a dependency list left
+    /// behind for the mounted instance they run for; `ComponentDidMount` effects don't use them.
     pub fn effects_runner(
         effect: Effects,
         component: &mut Component,
+        effect_runs: &mut Vec<EffectRun>,
         prev_state: &JsValue,
         prev_props: &JsValue,
     ) -> Result<(), Error> {
@@ -118,11 +322,115 @@ pub mod effects_mod {
                 return component_did_mount_runner(component, prev_state, prev_props);
             }
             Effects::ComponentDidUpdate => {
-                return component_did_update_runner(component, prev_state, prev_props, None, None)
+                let mut transitions = vec![component.get_state().to_owned()];
+                return component_did_update_runner(
+                    component,
+                    effect_runs,
+                    prev_state,
+                    prev_props,
+                    None,
+                    None,
+                    0,
+                    &mut transitions,
+                );
             }
             Effects::ComponentWillUnmount => {
-                return component_will_unmount_runner();
+                return component_will_unmount_runner(
+                    component,
+                    effect_runs,
+                    prev_state,
+                    prev_props,
+                );
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use wasm_bindgen_test::*;
+
+        use super::*;
+
+        wasm_bindgen_test_configure!(run_in_browser);
+
+        #[wasm_bindgen_test]
+        /// An effect record must only run when its dependencies change, and the cleanup returned from
+        /// its previous run must be called before it runs again.
+        fn test_run_effect_record() {
+            let callback = Function::new_no_args(
+                "globalThis.runs = (globalThis.runs || 0) + 1; return () => { globalThis.cleanups = (globalThis.cleanups || 0) + 1; };",
+            );
+            let deps_fn = Function::new_with_args("state, props", "return [state.count];");
+            let record: JsValue = create_effect_record(callback, deps_fn).into();
+            let args = Array::new();
+            let props = JSON::parse("{}").unwrap();
+            let global = web_sys::js_sys::global();
+            let mut run = EffectRun::default();
+
+            for count in ["{\"count\":1}", "{\"count\":1}", "{\"count\":2}"] {
+                let state = JSON::parse(count).unwrap();
+                let run_result = run_effect_record(&record, &mut run, &args, &state, &props);
+                assert!(matches!(run_result, Ok(value) if value.is_undefined()));
+            }
+            let runs = Reflect::get(&global, &"runs".into()).unwrap();
+            let cleanups = Reflect::get(&global, &"cleanups".into()).unwrap();
+            assert_eq!(runs.as_f64(), Some(2.0));
+            assert_eq!(cleanups.as_f64(), Some(1.0));
+        }
+
+        #[wasm_bindgen_test]
+        /// Two instances of a component, whose props differ, must keep the dependencies and cleanup of
+        /// an effect record apart: updating one instance doesn't rerun the effect of the other, and
+        /// unmounting one only calls its own cleanup.
+        fn test_effect_runs_per_instance() {
+            let callback = Function::new_with_args(
+                "props",
+                "(globalThis.instanceRuns ||= []).push(props.id); return () => (globalThis.instanceCleanups ||= []).push(props.id);",
+            );
+            let deps_fn = Function::new_with_args("state, props", "return [props.id];");
+            let mut component = Component::new("{}".to_owned(), String::new());
+            component.register_effect_with_deps(callback, deps_fn);
+            let mut first = component.clone();
+            first.set_props("{\"id\":1}".to_owned());
+            let mut second = component.clone();
+            second.set_props("{\"id\":2}".to_owned());
+            let mut first_runs: Vec<EffectRun> = Vec::new();
+            let mut second_runs: Vec<EffectRun> = Vec::new();
+
+            for _ in 0..2 {
+                for (instance, runs) in [
+                    (&mut first, &mut first_runs),
+                    (&mut second, &mut second_runs),
+                ] {
+                    let prev_state = instance.state_parsed();
+                    let prev_props = instance.props_parsed();
+                    let update_result = effects_runner(
+                        Effects::ComponentDidUpdate,
+                        instance,
+                        runs,
+                        &prev_state,
+                        &prev_props,
+                    );
+                    assert!(update_result.is_ok());
+                }
             }
+            let prev_state = second.state_parsed();
+            let prev_props = second.props_parsed();
+            let unmount_result = effects_runner(
+                Effects::ComponentWillUnmount,
+                &mut second,
+                &mut second_runs,
+                &prev_state,
+                &prev_props,
+            );
+            assert!(unmount_result.is_ok());
+
+            let global = web_sys::js_sys::global();
+            let runs = Reflect::get(&global, &"instanceRuns".into()).unwrap();
+            let cleanups = Reflect::get(&global, &"instanceCleanups".into()).unwrap();
+            assert_eq!(JSON::stringify(&runs).unwrap(), "[1,2]");
+            assert_eq!(JSON::stringify(&cleanups).unwrap(), "[2]");
+            assert!(first_runs[0].cleanup.is_some());
         }
     }
 }
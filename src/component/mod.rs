@@ -1,13 +1,22 @@
 mod effects;
+mod props;
 
 pub mod component_mod {
 
-    use std::{collections::HashMap, ops::Deref};
+    use std::{
+        collections::HashMap,
+        ops::Deref,
+        sync::atomic::{AtomicU32, Ordering},
+    };
 
     use crate::{
-        dom::dom_mod::construct_dom_wrapper,
-        error::error_mod::{error_handler, Error},
+        dom::dom_mod::{
+            construct_dom_wrapper, get_mounted_state, run_update_effects, unmount_component,
+            update_mounted_component,
+        },
+        error::error_mod::{diagnostic_handler, error_handler, Error},
         parser::parser_mod::{NodeType, VirtualNode},
+        tokenizer::tokenizer_mod::Span,
     };
     use serde::{Deserialize, Serialize};
     use serde_wasm_bindgen::{from_value, to_value};
@@ -18,14 +27,26 @@ pub mod component_mod {
     };
 
     use crate::{
-        parser::parser_mod::parse_vdom_from_string, presenter::presenter_mod::parse_presenter,
+        parser::parser_mod::{
+            is_recovering_from_errors, parse_vdom_from_string, parse_vdom_from_string_recovering,
+            set_keep_comments, set_recover_from_errors,
+        },
+        presenter::presenter_mod::parse_presenter,
+    };
+
+    pub use super::effects::effects_mod::{
+        create_effect_record, effects_runner, set_max_update_depth, EffectRun, Effects,
     };
+    pub use super::props::props_mod::{get_props_from_attributes, get_props_with_prop};
 
-    pub use super::effects::effects_mod::{effects_runner, Effects};
+    /// Source of `Component::id` values. Ids are handed out once, in the constructor; clones and
+    /// deserialized copies of a component keep the id of the object they were made from.
+    static NEXT_COMPONENT_ID: AtomicU32 = AtomicU32::new(0);
 
     #[derive(Serialize, Deserialize, Debug)]
     #[wasm_bindgen]
     pub struct Component {
+        id: u32,
         state: String,
         presenter: String,
         props: String,
@@ -36,11 +57,14 @@ pub mod component_mod {
         component_did_mount: Array,
         #[serde(with = "serde_wasm_bindgen::preserve")]
         component_will_unmount: Array,
+        #[serde(default)]
+        preserve_whitespace: bool,
     }
 
     impl Clone for Component {
         fn clone(&self) -> Self {
             Component {
+                id: self.id,
                 presenter: self.presenter.clone(),
                 props: self.props.to_owned(),
                 state: self.state.to_owned(),
@@ -48,6 +72,7 @@ pub mod component_mod {
                 component_will_unmount: self.component_will_unmount.clone(),
                 vdom: Box::from(self.vdom.deref().to_owned()),
                 effects: self.effects.clone(),
+                preserve_whitespace: self.preserve_whitespace,
             }
         }
     }
@@ -55,6 +80,10 @@ pub mod component_mod {
     // TODO: refactor as much clone() call you can with lifetime parameters.
 
     impl Component {
+        pub fn get_id(&self) -> u32 {
+            return self.id;
+        }
+
         pub fn get_vdom<'a>(&'a self) -> &'a Box<VirtualNode> {
             return &self.vdom;
         }
@@ -71,6 +100,10 @@ pub mod component_mod {
             return &self.component_did_mount;
         }
 
+        pub fn get_component_will_unmount<'a>(&'a self) -> &'a Array {
+            return &self.component_will_unmount;
+        }
+
         pub fn get_effects<'a>(&'a self) -> &'a Array {
             return &self.effects;
         }
@@ -79,6 +112,11 @@ pub mod component_mod {
             self.vdom = Box::new(v_node.clone());
         }
 
+        /// Replaces the state of the component with an already stringified state.
+        pub fn set_state_string(&mut self, state: String) {
+            self.state = state;
+        }
+
         pub fn effect_arr_into_vec(&self) -> Vec<JsValue> {
             let arr = &self.effects;
             let iter_arr = &arr.clone().into_iter();
@@ -116,8 +154,11 @@ pub mod component_mod {
                 attributes: HashMap::new(),
                 children: Vec::new(),
                 node_type: NodeType::Tag(" ".to_owned()),
+                span: Span::default(),
+                attribute_spans: HashMap::new(),
             });
             Component {
+                id: NEXT_COMPONENT_ID.fetch_add(1, Ordering::Relaxed),
                 state,
                 presenter,
                 props: "{}".to_owned(),
@@ -125,9 +166,15 @@ pub mod component_mod {
                 effects: Array::new(),
                 component_will_unmount: Array::new(),
                 component_did_mount: Array::new(),
+                preserve_whitespace: false,
             }
         }
 
+        #[wasm_bindgen(getter)]
+        pub fn id(&self) -> u32 {
+            self.id
+        }
+
         #[wasm_bindgen(getter)]
         pub fn component_did_mount(&self) -> Array {
             self.component_did_mount.clone()
@@ -200,6 +247,40 @@ pub mod component_mod {
             self.presenter = presenter;
         }
 
+        #[wasm_bindgen(getter)]
+        pub fn preserve_whitespace(&self) -> bool {
+            self.preserve_whitespace
+        }
+
+        #[wasm_bindgen(setter)]
+        /// Sets whether the whitespace in the texts of this component's presenter is kept exactly as it
+        /// is written, instead of being collapsed; takes effect the next time it is rendered.
+        pub fn set_preserve_whitespace(&mut self, preserve_whitespace: bool) {
+            self.preserve_whitespace = preserve_whitespace;
+        }
+
+        #[wasm_bindgen(js_name = set_max_update_depth)]
+        /// Sets how many times in a row effects may update the state of a component, before retort gives
+        /// up and reports a `MaxUpdateDepthExceeded` error; defaults to 50. Applies to every component.
+        pub fn set_max_update_depth_of_effects(depth: u32) {
+            set_max_update_depth(depth);
+        }
+
+        #[wasm_bindgen(js_name = set_keep_comments)]
+        /// Sets whether comments in presenters, like `<!-- note -->`, are rendered as comment nodes; they
+        /// are dropped by default. Applies to every presenter which is parsed afterwards.
+        pub fn set_keep_comments_of_presenters(keep: bool) {
+            set_keep_comments(keep);
+        }
+
+        #[wasm_bindgen(js_name = set_recover_from_parsing_errors)]
+        /// Sets whether presenters are parsed without stopping at the first error; every error and
+        /// warning is then logged to the console at once, and the rest of the presenter is rendered as
+        /// well as it can be. Applies to every presenter which is parsed afterwards.
+        pub fn set_recover_from_parsing_errors(recover: bool) {
+            set_recover_from_errors(recover);
+        }
+
         #[wasm_bindgen]
         /// adds the provided callback to component's effects list.
         pub fn register_effect(&mut self, callback: Function) {
@@ -209,6 +290,17 @@ pub mod component_mod {
             self.set_effects(prev.concat(&new_array));
         }
 
+        #[wasm_bindgen]
+        /// adds the provided callback to component's effects list; unlike `register_effect`, the callback
+        /// only runs when the values in the array returned by `deps_fn(state, props)` change. If the
+        /// callback returns a function, it is called before the next run of the callback and on unmount.
+        /// Dependencies and cleanups are kept for each mounted instance separately.
+        pub fn register_effect_with_deps(&mut self, callback: Function, deps_fn: Function) {
+            let prev = &self.effects;
+            let new_array: Array = Array::of1(&create_effect_record(callback, deps_fn));
+            self.set_effects(prev.concat(&new_array));
+        }
+
         #[wasm_bindgen]
         /// adds the provided callback to component's component_did_mount effects list.
         pub fn register_component_did_mount(&mut self, callback: Function) {
@@ -254,25 +346,39 @@ pub mod component_mod {
         /// Updates the `state` of a component which this function is called with, using a callback function.
         /// provided callback is called with component's current `state` as an argument, allowing user to
         /// return the component's next `state` accordingly.
-        /// NOTE that its logic is partially incomplete. after any state update, a repaint must be done.
+        /// Every mounted instance of the component is then re-evaluated against the new state and only
+        /// the parts of the DOM that changed are patched. `ComponentDidUpdate` effects then run for each
+        /// mounted instance, with its own props; if they return yet another state, the DOM is patched once
+        /// more after they have run.
         pub fn set_state(&mut self, callback: Function) {
+            // effects that ran while mounting may have updated the mounted copy of this component.
+            if let Some(mounted_state) = get_mounted_state(self.id) {
+                self.state = mounted_state;
+            }
             let prev_state = self.state_parsed();
             let new_state_result = self.set_state_inner(&prev_state, callback);
             if new_state_result.is_err() {
                 error_handler(new_state_result.unwrap_err());
+                return;
+            }
+            let update_result = update_mounted_component(self);
+            if update_result.is_err() {
+                error_handler(update_result.unwrap_err());
+                return;
             }
-            // diffing algorithm, DOM update, VDOM update and all other shenanigan here.
+            let state_before_effects = self.state.clone();
             time();
-            let result = effects_runner(
-                Effects::ComponentDidUpdate,
-                self,
-                &prev_state,
-                &self.props_parsed(),
-            );
-            // let result = self.run_effects(&prev_state, &self.props_parsed());
+            let result = run_update_effects(self, &prev_state);
             time_end();
             if result.is_err() {
                 error_handler(result.unwrap_err());
+                return;
+            }
+            if self.state != state_before_effects {
+                let update_result = update_mounted_component(self);
+                if update_result.is_err() {
+                    error_handler(update_result.unwrap_err());
+                }
             }
         }
 
@@ -288,7 +394,21 @@ pub mod component_mod {
             }
             let parsed_presenter = parsed_presenter_result.unwrap();
 
-            let vdom_result = parse_vdom_from_string(&parsed_presenter).await;
+            if is_recovering_from_errors() {
+                let (virtual_node, diagnostics) = parse_vdom_from_string_recovering(
+                    &parsed_presenter,
+                    component.preserve_whitespace,
+                )
+                .await;
+                for diagnostic in &diagnostics {
+                    diagnostic_handler(diagnostic);
+                }
+                component.set_vdom(&virtual_node);
+                return Ok(());
+            }
+
+            let vdom_result =
+                parse_vdom_from_string(&parsed_presenter, component.preserve_whitespace).await;
 
             if let Result::Err(err) = vdom_result {
                 return Err(err);
@@ -315,22 +435,23 @@ pub mod component_mod {
         /// that wraps the entire component tree, otherwise a subtree of components will be added to DOM.
         pub fn mount(&mut self) {
             time_with_label("total render time:");
-
-            // currently, root component is not being recognized as a `Component` object in vdom; this
-            // introduces some problems to overall logic. So as a workaround, we change its `node_type` type manually
-            // to `Component`. HOWEVER, i'm not sure this is the best solution.
-
-            self.set_vdom(&VirtualNode {
-                attributes: HashMap::new(), // root component should have no props.
-                children: Vec::new(),       // children for component in inheritably not supported
-                node_type: NodeType::Component(self.clone()), // change node type of root from Tag to Component
-            });
-
             let res = construct_dom_wrapper(self);
             time_end_with_label("total render time:");
             if res.is_err() {
                 error_handler(res.unwrap_err());
             }
         }
+
+        #[wasm_bindgen]
+        /// Removes every mounted instance of this component from the DOM. `ComponentWillUnmount` effects
+        /// of nested components run first, then the ones of this component; afterwards, the DOM nodes
+        /// are removed and their event listeners are released. Calling `mount` on the root component
+        /// again renders it from scratch.
+        pub fn unmount(&mut self) {
+            let res = unmount_component(self);
+            if res.is_err() {
+                error_handler(res.unwrap_err());
+            }
+        }
     }
 }
@@ -0,0 +1,132 @@
+/// Contains the logic of turning the attributes of a component tag into the props of that component.
+/// Props of a component are kept as a JSON string, and so is the evaluated value of each attribute.
+pub mod props_mod {
+    use std::collections::HashMap;
+
+    use serde_json::{from_str, to_string, Map, Value};
+
+    use crate::{const_util::const_util_mod::is_input_key_attribute, error::error_mod::Error};
+
+    /// Parses `value` as JSON; an `Err` variant mentioning `name` is returned if it is not valid.
+    fn parse_json(name: &str, value: &str) -> Result<Value, Error> {
+        let parsed_result: Result<Value, serde_json::Error> = from_str(value);
+        if parsed_result.is_err() {
+            return Err(Error::TypeError(format!(
+                "Expected `{name}` to be valid JSON, but it was: {value}"
+            )));
+        }
+        Ok(parsed_result.unwrap())
+    }
+
+    /// Parses `props` into a JSON object. An `Err` variant is returned if `props` is not a valid
+    /// JSON object.
+    fn parse_props(props: &str) -> Result<Map<String, Value>, Error> {
+        let parsed_result = parse_json("props", props);
+        if parsed_result.is_err() {
+            return Err(parsed_result.unwrap_err());
+        }
+        match parsed_result.unwrap() {
+            Value::Object(map) => Ok(map),
+            _ => Err(Error::TypeError(format!(
+                "Expected props to be a JSON object, but they were: {props}"
+            ))),
+        }
+    }
+
+    /// Stringifies a JSON object of props.
+    fn stringify_props(props: &Map<String, Value>) -> Result<String, Error> {
+        let stringified_result = to_string(props);
+        if stringified_result.is_err() {
+            return Err(Error::TypeError(
+                "Failed to stringify the props of a component.".to_owned(),
+            ));
+        }
+        Ok(stringified_result.unwrap())
+    }
+
+    /// Given the evaluated attributes of a component tag, where each value is a JSON string, returns
+    /// the props of that component as a JSON string. The `key` attribute only identifies the tag among
+    /// its siblings, so it doesn't become a prop.
+    pub fn get_props_from_attributes(
+        attributes: &HashMap<String, String>,
+    ) -> Result<String, Error> {
+        let mut props: Map<String, Value> = Map::new();
+        for (key, value) in attributes {
+            if is_input_key_attribute(key) {
+                continue;
+            }
+            let parsed_result = parse_json(key, value);
+            if parsed_result.is_err() {
+                return Err(parsed_result.unwrap_err());
+            }
+            props.insert(key.to_owned(), parsed_result.unwrap());
+        }
+        stringify_props(&props)
+    }
+
+    /// Returns a copy of `props` in which the prop named `key` is set to `value`, which must be a JSON
+    /// string; or is removed, if `value` is `None`.
+    pub fn get_props_with_prop(
+        props: &str,
+        key: &str,
+        value: Option<&String>,
+    ) -> Result<String, Error> {
+        let props_result = parse_props(props);
+        if props_result.is_err() {
+            return Err(props_result.unwrap_err());
+        }
+        let mut props = props_result.unwrap();
+        if value.is_some() {
+            let parsed_result = parse_json(key, value.unwrap());
+            if parsed_result.is_err() {
+                return Err(parsed_result.unwrap_err());
+            }
+            props.insert(key.to_owned(), parsed_result.unwrap());
+        } else {
+            props.remove(key);
+        }
+        stringify_props(&props)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        /// `get_props_from_attributes` must keep the JSON type of each value and leave `key` out.
+        fn test_get_props_from_attributes() {
+            let attributes: HashMap<String, String> = HashMap::from([
+                ("title".to_owned(), "\"hi\"".to_owned()),
+                ("count".to_owned(), "2".to_owned()),
+                ("items".to_owned(), "[1,{\"a\":true}]".to_owned()),
+                ("key".to_owned(), "k1".to_owned()),
+            ]);
+            let props_result = get_props_from_attributes(&attributes);
+            assert!(
+                matches!(props_result, Ok(props) if props == "{\"count\":2,\"items\":[1,{\"a\":true}],\"title\":\"hi\"}")
+            );
+        }
+
+        #[test]
+        /// `get_props_from_attributes` must fail when a value is not valid JSON.
+        fn test_get_props_from_attributes_invalid() {
+            let attributes: HashMap<String, String> =
+                HashMap::from([("title".to_owned(), "hi".to_owned())]);
+            let props_result = get_props_from_attributes(&attributes);
+            assert!(matches!(props_result, Err(Error::TypeError(msg)) if msg.contains("title")));
+        }
+
+        #[test]
+        /// `get_props_with_prop` must add, replace and remove single props.
+        fn test_get_props_with_prop() {
+            let props = "{\"a\":1}";
+            let props_result = get_props_with_prop(props, "b", Some(&"[true]".to_owned()));
+            assert!(matches!(&props_result, Ok(props) if props == "{\"a\":1,\"b\":[true]}"));
+            let props_result =
+                get_props_with_prop(&props_result.unwrap(), "a", Some(&"\"x\"".to_owned()));
+            assert!(matches!(&props_result, Ok(props) if props == "{\"a\":\"x\",\"b\":[true]}"));
+            let props_result = get_props_with_prop(&props_result.unwrap(), "b", None);
+            assert!(matches!(props_result, Ok(props) if props == "{\"a\":\"x\"}"));
+        }
+    }
+}
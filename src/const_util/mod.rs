@@ -3,6 +3,9 @@ pub mod const_util_mod {
     pub const RENDER_IF_ATTRIBUTE_NAME: &str = "render-if";
     pub const RENDER_ELSE_IF_ATTRIBUTE_NAME: &str = "render-else-if";
     pub const RENDER_ELSE_ATTRIBUTE_NAME: &str = "render-else";
+    pub const RENDER_FOR_ATTRIBUTE_NAME: &str = "render-for";
+    pub const LOOP_SEPARATOR: &str = " of ";
+    pub const KEY_ATTRIBUTE_NAME: &str = "key";
     pub const DOM_ERROR: &str = "DOM error";
     pub const PARSING_ERROR: &str = "Parsing error";
     pub const REFERENCE_ERROR: &str = "Reference error";
@@ -11,6 +14,9 @@ pub mod const_util_mod {
     pub const _INVESTIGATION_NEEDED_ERROR: &str = "Unknown error";
     pub const SERDE_WASM_BINDGEN_ERROR: &str = "Serialization error";
     pub const RESOLVE_ERROR: &str = "Resolve error";
+    pub const MAX_UPDATE_DEPTH_ERROR: &str = "Maximum update depth exceeded";
+    pub const MISSING_KEY_WARNING: &str = "Missing key";
+    pub const DUPLICATE_KEY_WARNING: &str = "Duplicate key";
     pub const ERROR_WRAPPER_STYLES: &str = "
                                             line-height:30px;
                                             background-color:#570606;
@@ -30,11 +36,19 @@ pub mod const_util_mod {
     pub const USE_STRICT: &str = "\"use strict\";";
     pub const STATE_PARAMETER: &str = "state_";
     pub const PROPS_PARAMETER: &str = "props_";
+    pub const EVENT_PARAMETER: &str = "event";
+    pub const SET_STATE_PARAMETER: &str = "set_state";
+    pub const EVENT_ATTRIBUTE_PREFIX: &str = "on";
+    pub const DEFAULT_MAX_UPDATE_DEPTH: u32 = 50;
+    pub const STATE_TRANSITIONS_LIMIT: usize = 5;
+    pub const EFFECT_CALLBACK_KEY: &str = "callback";
+    pub const EFFECT_DEPS_FN_KEY: &str = "deps_fn";
     /// In JSON strings which contain arrays, `stringify` method is called twice
     /// when converting. Since we need to call the `parse` as many times as we have called the `stringify`,
-    /// we must check whether the type of state is `object` or not after the first call to `parse`.
+    /// we must check whether the type of state and props is `object` or not after the first call to `parse`.
     pub const CLOSURE: &str =
-        "let state=JSON.parse(state_);if(typeof state === 'string'){state=JSON.parse(state)}";
+        "let state=JSON.parse(state_);if(typeof state === 'string'){state=JSON.parse(state)}\
+        let props=JSON.parse(props_);if(typeof props === 'string'){props=JSON.parse(props)}";
     pub const RETURN: &str = "return ";
     pub const UNDEFINED_LITERAL: &str = "undefined";
     pub const NULL_LITERAL: &str = "null";
@@ -42,12 +56,179 @@ pub mod const_util_mod {
     pub const CLOSE_CURLY_BRACKET: char = '}';
     pub const OPEN_ANGLE_BRACKET: char = '<';
     pub const CLOSE_ANGLE_BRACKET: char = '>';
+    #[cfg(any(test, feature = "legacy-tokenizer"))]
     const WHITESPACE_ALIAS: &str = "";
+    #[cfg(any(test, feature = "legacy-tokenizer"))]
     const FORWARD_SLASH: &str = "/";
     pub const SELF_CLOSING_TAG: &str = "/>";
     pub const CLOSING_TAG: &str = "</";
+    pub const FRAGMENT_TAG_NAME: &str = ""; // `<>` and `</>` are tags without a name
+    pub const COMMENT_START: &str = "<!--";
+    pub const COMMENT_END: &str = "-->";
     pub const ATTRIBUTE_KEY_VALUE_SEPARATOR: &str = "=";
+    pub const BOOLEAN_ATTRIBUTE_VALUE: &str = "{true}";
     pub const IMPORT_KEYWORD: &str = "import";
+    pub const SLOT_TAG_NAME: &str = "slot";
+    pub const SLOT_NAME_ATTRIBUTE_NAME: &str = "name";
+    pub const SLOT_ATTRIBUTE_NAME: &str = "slot";
+    pub const SLOT_SCOPE_ATTRIBUTE_NAME: &str = "slot-scope";
+    pub const DEFAULT_SLOT_NAME: &str = ""; // `<slot />` without a name
+    pub const SVG_TAG_NAME: &str = "svg";
+    pub const MATH_TAG_NAME: &str = "math";
+    pub const FOREIGN_OBJECT_TAG_NAME: &str = "foreignObject";
+    pub const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+    pub const MATHML_NAMESPACE: &str = "http://www.w3.org/1998/Math/MathML";
+    pub const VOID_ELEMENTS: [&str; 13] = [
+        "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source",
+        "track", "wbr",
+    ];
+    /// Events which elements fire, so that `on` followed by one of them, like `onclick`, is an event
+    /// attribute.
+    pub const EVENT_NAMES: [&str; 87] = [
+        "abort",
+        "animationcancel",
+        "animationend",
+        "animationiteration",
+        "animationstart",
+        "auxclick",
+        "beforeinput",
+        "blur",
+        "cancel",
+        "canplay",
+        "canplaythrough",
+        "change",
+        "click",
+        "close",
+        "contextmenu",
+        "copy",
+        "cut",
+        "dblclick",
+        "drag",
+        "dragend",
+        "dragenter",
+        "dragleave",
+        "dragover",
+        "dragstart",
+        "drop",
+        "durationchange",
+        "emptied",
+        "ended",
+        "error",
+        "focus",
+        "focusin",
+        "focusout",
+        "formdata",
+        "input",
+        "invalid",
+        "keydown",
+        "keypress",
+        "keyup",
+        "load",
+        "loadeddata",
+        "loadedmetadata",
+        "loadstart",
+        "mousedown",
+        "mouseenter",
+        "mouseleave",
+        "mousemove",
+        "mouseout",
+        "mouseover",
+        "mouseup",
+        "paste",
+        "pause",
+        "play",
+        "playing",
+        "pointercancel",
+        "pointerdown",
+        "pointerenter",
+        "pointerleave",
+        "pointermove",
+        "pointerout",
+        "pointerover",
+        "pointerup",
+        "progress",
+        "ratechange",
+        "reset",
+        "resize",
+        "scroll",
+        "scrollend",
+        "seeked",
+        "seeking",
+        "select",
+        "selectionchange",
+        "stalled",
+        "submit",
+        "suspend",
+        "timeupdate",
+        "toggle",
+        "touchcancel",
+        "touchend",
+        "touchmove",
+        "touchstart",
+        "transitioncancel",
+        "transitionend",
+        "transitionrun",
+        "transitionstart",
+        "volumechange",
+        "waiting",
+        "wheel",
+    ];
+    /// Elements whose content is text which is never parsed, so it may contain `<` and `{`.
+    pub const RAW_TEXT_ELEMENTS: [&str; 2] = ["script", "style"];
+    /// Elements whose content is never parsed as markup either, but may contain expressions and
+    /// character references.
+    pub const ESCAPABLE_RAW_TEXT_ELEMENTS: [&str; 2] = ["textarea", "title"];
+    /// Elements whose texts are kept exactly as they are written, whitespace included.
+    pub const WHITESPACE_PRESERVING_ELEMENTS: [&str; 2] = ["pre", "textarea"];
+    /// Named character references which can be used in text and static attribute values, like `&amp;`.
+    pub const NAMED_CHARACTER_REFERENCES: [(&str, char); 46] = [
+        ("amp", '&'),
+        ("lt", '<'),
+        ("gt", '>'),
+        ("quot", '"'),
+        ("apos", '\''),
+        ("nbsp", '\u{a0}'),
+        ("lbrace", '{'),
+        ("rbrace", '}'),
+        ("lcub", '{'),
+        ("rcub", '}'),
+        ("copy", '©'),
+        ("reg", '®'),
+        ("trade", '™'),
+        ("hellip", '…'),
+        ("ndash", '–'),
+        ("mdash", '—'),
+        ("lsquo", '‘'),
+        ("rsquo", '’'),
+        ("ldquo", '“'),
+        ("rdquo", '”'),
+        ("laquo", '«'),
+        ("raquo", '»'),
+        ("bull", '•'),
+        ("middot", '·'),
+        ("deg", '°'),
+        ("plusmn", '±'),
+        ("times", '×'),
+        ("divide", '÷'),
+        ("euro", '€'),
+        ("pound", '£'),
+        ("yen", '¥'),
+        ("cent", '¢'),
+        ("sect", '§'),
+        ("para", '¶'),
+        ("larr", '←'),
+        ("rarr", '→'),
+        ("uarr", '↑'),
+        ("darr", '↓'),
+        ("harr", '↔'),
+        ("hearts", '♥'),
+        ("ensp", '\u{2002}'),
+        ("emsp", '\u{2003}'),
+        ("thinsp", '\u{2009}'),
+        ("zwnj", '\u{200c}'),
+        ("zwj", '\u{200d}'),
+        ("shy", '\u{ad}'),
+    ];
     const FROM_KEYWORD: &str = "from";
     const TRUE_LITERAL: &str = "true";
 
@@ -56,6 +237,79 @@ pub mod const_util_mod {
         return input == TRUE_LITERAL;
     }
 
+    /// returns true if the `input` parameter is one of the `render-*` special attributes.
+    pub fn is_input_render_attribute(input: &str) -> bool {
+        return input == RENDER_IF_ATTRIBUTE_NAME
+            || input == RENDER_ELSE_IF_ATTRIBUTE_NAME
+            || input == RENDER_ELSE_ATTRIBUTE_NAME
+            || input == RENDER_FOR_ATTRIBUTE_NAME;
+    }
+
+    /// returns true if the `input` parameter is equal to predefined `KEY_ATTRIBUTE_NAME` constant.
+    pub fn is_input_key_attribute(input: &str) -> bool {
+        return input == KEY_ATTRIBUTE_NAME;
+    }
+
+    /// returns true if the `input` parameter is a name which the evaluator already uses, thus can't be
+    /// bound to anything else.
+    pub fn is_input_reserved_parameter(input: &str) -> bool {
+        return input == STATE_PARAMETER
+            || input == PROPS_PARAMETER
+            || input == "state"
+            || input == "props"
+            || input == EVENT_PARAMETER
+            || input == SET_STATE_PARAMETER;
+    }
+
+    /// returns true if the `input` parameter is the name of an event attribute, like `onclick`; i.e. `on`
+    /// followed by the name of an event. Attributes like `one` or `online` are not.
+    pub fn is_input_event_attribute(input: &str) -> bool {
+        let input = input.to_lowercase();
+        let event_name = input.strip_prefix(EVENT_ATTRIBUTE_PREFIX);
+        return event_name.is_some_and(|event_name| EVENT_NAMES.contains(&event_name));
+    }
+
+    /// returns true if the `input` parameter is the name of an HTML void element, like `br`, which can't
+    /// have children and is never closed.
+    pub fn is_input_void_element(input: &str) -> bool {
+        return VOID_ELEMENTS
+            .iter()
+            .any(|element| element.eq_ignore_ascii_case(input));
+    }
+
+    /// returns true if the `input` parameter is the name of an element whose content is read as raw
+    /// text, like `style`.
+    pub fn is_input_raw_text_element(input: &str) -> bool {
+        return RAW_TEXT_ELEMENTS
+            .iter()
+            .any(|element| element.eq_ignore_ascii_case(input));
+    }
+
+    /// returns true if the `input` parameter is the name of an element whose content is read as
+    /// escapable raw text, like `textarea`.
+    pub fn is_input_escapable_raw_text_element(input: &str) -> bool {
+        return ESCAPABLE_RAW_TEXT_ELEMENTS
+            .iter()
+            .any(|element| element.eq_ignore_ascii_case(input));
+    }
+
+    /// returns true if the `input` parameter is the name of an element whose whitespace is preserved,
+    /// like `pre`.
+    pub fn is_input_whitespace_preserving_element(input: &str) -> bool {
+        return WHITESPACE_PRESERVING_ELEMENTS
+            .iter()
+            .any(|element| element.eq_ignore_ascii_case(input));
+    }
+
+    /// returns the char which the named character reference `name`, like `amp` for `&amp;`, stands for;
+    /// or `None` if `name` is not one of `NAMED_CHARACTER_REFERENCES`.
+    pub fn get_named_character_reference(name: &str) -> Option<char> {
+        NAMED_CHARACTER_REFERENCES
+            .iter()
+            .find(|(reference, _)| *reference == name)
+            .map(|(_, chr)| *chr)
+    }
+
     /// returns true if the `input` parameter is equal to predefined `UNDEFINED_LITERAL` constant.
     pub fn is_input_undefined_literal(input: &str) -> bool {
         return input == UNDEFINED_LITERAL;
@@ -71,6 +325,11 @@ pub mod const_util_mod {
         return input == OPEN_CURLY_BRACKET;
     }
 
+    /// returns true if the `input` parameter is a quotation mark, either `"` or `'`.
+    pub fn is_input_quotation_mark(input: char) -> bool {
+        return input == '"' || input == '\'';
+    }
+
     /// returns true if the `input` parameter is equal to predefined `CLOSE_CURLY_BRACKET` constant.
     pub fn is_input_close_curly_bracket(input: char) -> bool {
         return input == CLOSE_CURLY_BRACKET;
@@ -87,16 +346,19 @@ pub mod const_util_mod {
     }
 
     /// returns true if the `input` parameter is equal to predefined `WHITESPACE_ALIAS` constant.
+    #[cfg(any(test, feature = "legacy-tokenizer"))]
     pub fn is_input_white_space_alias(input: &str) -> bool {
         return input == WHITESPACE_ALIAS;
     }
 
     /// returns true if the `input` parameter is equal to predefined `ATTRIBUTE_KEY_VALUE_SEPARATOR` constant.
+    #[cfg(any(test, feature = "legacy-tokenizer"))]
     pub fn is_input_attribute_key_value_separator(input: &str) -> bool {
         return input == ATTRIBUTE_KEY_VALUE_SEPARATOR;
     }
 
     /// returns true if the `input` parameter is equal to predefined `FORWARD_SLASH` constant.
+    #[cfg(any(test, feature = "legacy-tokenizer"))]
     pub fn is_input_forward_slash(input: &str) -> bool {
         return input == FORWARD_SLASH;
     }
@@ -0,0 +1,668 @@
+/// Compares evaluated virtual nodes and describes their differences as a list of patches. This module
+/// doesn't touch the DOM; applying the patches is left to the `dom` module.
+pub mod diff_mod {
+    use std::collections::{HashMap, HashSet, VecDeque};
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        const_util::const_util_mod::{is_input_key_attribute, KEY_ATTRIBUTE_NAME},
+        parser::parser_mod::{NodeType, VirtualNode},
+    };
+
+    /// A single change which turns an old tree into a new one. `path` points to a node by the index
+    /// of each of its ancestors among their siblings, and `parent` points to the node whose children
+    /// are changed. Patches are meant to be applied in the order they are returned; each path refers
+    /// to the tree as it is after the previous patches have been applied.
+    #[derive(Serialize, Deserialize, Debug, Clone)]
+    pub enum Patch {
+        /// Inserts `node` as the `index`th child of `parent`.
+        Insert {
+            parent: Vec<usize>,
+            index: usize,
+            node: VirtualNode,
+        },
+        /// Removes the `index`th child of `parent`.
+        Remove {
+            parent: Vec<usize>,
+            index: usize,
+        },
+        /// Replaces the node at `path` with `node`.
+        Replace {
+            path: Vec<usize>,
+            node: VirtualNode,
+        },
+        SetAttribute {
+            path: Vec<usize>,
+            key: String,
+            value: String,
+        },
+        RemoveAttribute {
+            path: Vec<usize>,
+            key: String,
+        },
+        SetText {
+            path: Vec<usize>,
+            text: String,
+        },
+        /// Takes the `from`th child of `parent` out and puts it back so that it becomes the `to`th one.
+        Move {
+            parent: Vec<usize>,
+            from: usize,
+            to: usize,
+        },
+        /// Replaces the children passed to the component at `path`, i.e. what its `<slot />` renders.
+        SetSlot {
+            path: Vec<usize>,
+            children: Vec<VirtualNode>,
+        },
+    }
+
+    /// What a child is matched by when its siblings are keyed: its `key` attribute if it has one, or
+    /// its position among the siblings which don't.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    enum Identity {
+        Key(String),
+        Position(usize),
+    }
+
+    /// A child in the list that is being reordered; either one of the old children, or a new one that
+    /// has been inserted. Both are referred to by their index in the list they come from.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Slot {
+        Old(usize),
+        New(usize),
+    }
+
+    /// Returns true if any of `children` has a `key` attribute.
+    fn is_keyed(children: &Vec<VirtualNode>) -> bool {
+        children
+            .iter()
+            .any(|child| child.attributes.contains_key(KEY_ATTRIBUTE_NAME))
+    }
+
+    /// Returns the identity of each of `children`, in order.
+    fn get_identities(children: &Vec<VirtualNode>) -> Vec<Identity> {
+        let mut unkeyed_count: usize = 0;
+        let mut identities: Vec<Identity> = Vec::new();
+        for child in children {
+            let key = child.attributes.get(KEY_ATTRIBUTE_NAME);
+            if key.is_some() {
+                identities.push(Identity::Key(key.unwrap().to_owned()));
+            } else {
+                identities.push(Identity::Position(unkeyed_count));
+                unkeyed_count += 1;
+            }
+        }
+        identities
+    }
+
+    /// Returns the values of the longest strictly increasing subsequence of `sequence`. The nodes
+    /// whose old indexes are in this subsequence are already in the right order, so they can stay
+    /// where they are while the others are moved around them.
+    fn get_longest_increasing_subsequence(sequence: &Vec<usize>) -> HashSet<usize> {
+        // `tails[l]` is the position of the smallest value which ends an increasing subsequence of
+        // length `l + 1`, and `previous[p]` is the position before `p` in the subsequence ending at `p`.
+        let mut tails: Vec<usize> = Vec::new();
+        let mut previous: Vec<Option<usize>> = vec![None; sequence.len()];
+        for (position, value) in sequence.iter().enumerate() {
+            let length = tails.partition_point(|tail| sequence[*tail] < *value);
+            if length > 0 {
+                previous[position] = Some(tails[length - 1]);
+            }
+            if length == tails.len() {
+                tails.push(position);
+            } else {
+                tails[length] = position;
+            }
+        }
+        let mut result: HashSet<usize> = HashSet::new();
+        let mut current = tails.last().copied();
+        while current.is_some() {
+            let position = current.unwrap();
+            result.insert(sequence[position]);
+            current = previous[position];
+        }
+        result
+    }
+
+    /// Returns true if `new` can be obtained by patching `old` in place, i.e. both are texts, tags
+    /// with the same name, the same component or comments with the same content; comments never
+    /// change, since their content is not evaluated.
+    fn is_same_kind(old: &VirtualNode, new: &VirtualNode) -> bool {
+        match (&old.node_type, &new.node_type) {
+            (NodeType::Text(_), NodeType::Text(_)) => true,
+            (NodeType::Comment(old_comment), NodeType::Comment(new_comment)) => {
+                old_comment == new_comment
+            }
+            (NodeType::Tag(old_tag), NodeType::Tag(new_tag)) => old_tag == new_tag,
+            (NodeType::Component(old_component), NodeType::Component(new_component)) => {
+                old_component.get_id() == new_component.get_id()
+            }
+            _ => false,
+        }
+    }
+
+    /// Adds a patch for each attribute that was added, changed or removed. Keys are visited in
+    /// sorted order so the result doesn't depend on the order of `HashMap` iteration.
+    fn diff_attributes(
+        old: &VirtualNode,
+        new: &VirtualNode,
+        path: &Vec<usize>,
+        patches: &mut Vec<Patch>,
+    ) {
+        // keys only identify nodes and never reach the DOM.
+        let mut new_keys: Vec<&String> = new
+            .attributes
+            .keys()
+            .filter(|key| !is_input_key_attribute(key))
+            .collect();
+        new_keys.sort();
+        for key in new_keys {
+            let value = &new.attributes[key];
+            if old.attributes.get(key) == Some(value) {
+                continue;
+            }
+            patches.push(Patch::SetAttribute {
+                path: path.clone(),
+                key: key.to_owned(),
+                value: value.to_owned(),
+            });
+        }
+        let mut old_keys: Vec<&String> = old
+            .attributes
+            .keys()
+            .filter(|key| !is_input_key_attribute(key))
+            .collect();
+        old_keys.sort();
+        for key in old_keys {
+            if new.attributes.contains_key(key) {
+                continue;
+            }
+            patches.push(Patch::RemoveAttribute {
+                path: path.clone(),
+                key: key.to_owned(),
+            });
+        }
+    }
+
+    /// Adds the patches which turn the keyed children of the node at `path` from `old` into `new`.
+    /// Children are matched by their identity, so a reordered child is moved instead of being
+    /// rebuilt. Unmatched old children are removed first; then new children are placed from the last
+    /// one to the first, each right before its next sibling. Children which are part of the longest
+    /// run that kept its order are never moved.
+    fn diff_keyed_children_at(
+        old: &Vec<VirtualNode>,
+        new: &Vec<VirtualNode>,
+        path: &mut Vec<usize>,
+        patches: &mut Vec<Patch>,
+    ) {
+        let mut available: HashMap<Identity, VecDeque<usize>> = HashMap::new();
+        for (index, identity) in get_identities(old).into_iter().enumerate() {
+            available.entry(identity).or_default().push_back(index);
+        }
+        let matches: Vec<Option<usize>> = get_identities(new)
+            .iter()
+            .map(|identity| available.get_mut(identity).and_then(|old| old.pop_front()))
+            .collect();
+
+        let mut is_kept: Vec<bool> = vec![false; old.len()];
+        for old_index in matches.iter().flatten() {
+            is_kept[*old_index] = true;
+        }
+        for index in (0..old.len()).rev() {
+            if !is_kept[index] {
+                patches.push(Patch::Remove {
+                    parent: path.clone(),
+                    index,
+                });
+            }
+        }
+
+        let mut current: Vec<Slot> = (0..old.len())
+            .filter(|index| is_kept[*index])
+            .map(Slot::Old)
+            .collect();
+        let get_slot = |new_index: usize| match matches[new_index] {
+            Some(old_index) => Slot::Old(old_index),
+            None => Slot::New(new_index),
+        };
+        let position_of = |current: &Vec<Slot>, slot: Slot| {
+            current.iter().position(|other| *other == slot).unwrap()
+        };
+        let matched_old_indexes: Vec<usize> = matches.iter().flatten().copied().collect();
+        let stationary = get_longest_increasing_subsequence(&matched_old_indexes);
+
+        for index in (0..new.len()).rev() {
+            let next_position = if index + 1 < new.len() {
+                position_of(&current, get_slot(index + 1))
+            } else {
+                current.len()
+            };
+            if matches[index].is_none() {
+                patches.push(Patch::Insert {
+                    parent: path.clone(),
+                    index: next_position,
+                    node: new[index].clone(),
+                });
+                current.insert(next_position, Slot::New(index));
+                continue;
+            }
+            let old_index = matches[index].unwrap();
+            let from = position_of(&current, Slot::Old(old_index));
+            if !stationary.contains(&old_index) {
+                let to = if from < next_position {
+                    next_position - 1
+                } else {
+                    next_position
+                };
+                if from != to {
+                    patches.push(Patch::Move {
+                        parent: path.clone(),
+                        from,
+                        to,
+                    });
+                    let slot = current.remove(from);
+                    current.insert(to, slot);
+                }
+            }
+            path.push(position_of(&current, Slot::Old(old_index)));
+            diff_node(&old[old_index], &new[index], path, patches);
+            path.pop();
+        }
+    }
+
+    /// Adds the patches which turn the children of the node at `path` from `old` into `new`.
+    /// Children are matched by their identity if any of them has a `key` attribute, and by their
+    /// position otherwise.
+    fn diff_children_at(
+        old: &Vec<VirtualNode>,
+        new: &Vec<VirtualNode>,
+        path: &mut Vec<usize>,
+        patches: &mut Vec<Patch>,
+    ) {
+        if is_keyed(old) || is_keyed(new) {
+            diff_keyed_children_at(old, new, path, patches);
+            return;
+        }
+        let common_length = old.len().min(new.len());
+        for index in 0..common_length {
+            path.push(index);
+            diff_node(&old[index], &new[index], path, patches);
+            path.pop();
+        }
+        for index in (common_length..old.len()).rev() {
+            patches.push(Patch::Remove {
+                parent: path.clone(),
+                index,
+            });
+        }
+        for index in common_length..new.len() {
+            patches.push(Patch::Insert {
+                parent: path.clone(),
+                index,
+                node: new[index].clone(),
+            });
+        }
+    }
+
+    /// Adds the patches which turn the node at `path` from `old` into `new`.
+    fn diff_node(
+        old: &VirtualNode,
+        new: &VirtualNode,
+        path: &mut Vec<usize>,
+        patches: &mut Vec<Patch>,
+    ) {
+        if !is_same_kind(old, new) {
+            patches.push(Patch::Replace {
+                path: path.clone(),
+                node: new.clone(),
+            });
+            return;
+        }
+        if let (NodeType::Text(old_text), NodeType::Text(new_text)) =
+            (&old.node_type, &new.node_type)
+        {
+            if old_text != new_text {
+                patches.push(Patch::SetText {
+                    path: path.clone(),
+                    text: new_text.to_owned(),
+                });
+            }
+            return;
+        }
+        diff_attributes(old, new, path, patches);
+        if let NodeType::Component(_) = new.node_type {
+            // the children of a component are rendered by the component itself, wherever its
+            // `<slot>`s are, so they can't be patched in place. Scoped slot content is never of the
+            // same kind as anything, so it is always passed again; its result also depends on the
+            // state of the component which passed it.
+            if !diff_children(&old.children, &new.children).is_empty() {
+                patches.push(Patch::SetSlot {
+                    path: path.clone(),
+                    children: new.children.clone(),
+                });
+            }
+            return;
+        }
+        diff_children_at(&old.children, &new.children, path, patches);
+    }
+
+    /// Returns the patches which turn the evaluated tree `old` into `new`. Paths are relative to the
+    /// root, so the root itself is at the empty path. This is the public entry point for diffing a
+    /// single tree; rerendering a component diffs its roots with `diff_children` instead.
+    pub fn diff(old: &VirtualNode, new: &VirtualNode) -> Vec<Patch> {
+        let mut patches: Vec<Patch> = Vec::new();
+        diff_node(old, new, &mut Vec::new(), &mut patches);
+        patches
+    }
+
+    /// Returns the patches which turn the list of evaluated trees `old` into `new`, as if they were
+    /// the children of a node at the empty path. This is useful for components, which may evaluate
+    /// to no node at all.
+    pub fn diff_children(old: &Vec<VirtualNode>, new: &Vec<VirtualNode>) -> Vec<Patch> {
+        let mut patches: Vec<Patch> = Vec::new();
+        diff_children_at(old, new, &mut Vec::new(), &mut patches);
+        patches
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::collections::HashMap;
+
+        use crate::tokenizer::tokenizer_mod::Span;
+
+        use super::*;
+
+        fn tag(
+            name: &str,
+            attributes: Vec<(&str, &str)>,
+            children: Vec<VirtualNode>,
+        ) -> VirtualNode {
+            VirtualNode {
+                node_type: NodeType::Tag(name.to_owned()),
+                attributes: attributes
+                    .into_iter()
+                    .map(|(key, value)| (key.to_owned(), value.to_owned()))
+                    .collect(),
+                children,
+                span: Span::default(),
+                attribute_spans: HashMap::new(),
+            }
+        }
+
+        fn text(content: &str) -> VirtualNode {
+            VirtualNode {
+                node_type: NodeType::Text(content.to_owned()),
+                attributes: HashMap::new(),
+                children: Vec::new(),
+                span: Span::default(),
+                attribute_spans: HashMap::new(),
+            }
+        }
+
+        #[test]
+        /// Identical trees must not produce any patches.
+        fn test_diff_identical_trees() {
+            let old = tag(
+                "div",
+                vec![("id", "a")],
+                vec![tag("p", vec![], vec![text("hi")])],
+            );
+            let patches = diff(&old, &old.clone());
+            assert!(patches.is_empty());
+        }
+
+        #[test]
+        /// A changed text must be patched in place, at the path of the text node.
+        fn test_diff_set_text() {
+            let old = tag("div", vec![], vec![tag("p", vec![], vec![text("1")])]);
+            let new = tag("div", vec![], vec![tag("p", vec![], vec![text("2")])]);
+            let patches = diff(&old, &new);
+            assert_eq!(patches.len(), 1);
+            assert!(
+                matches!(&patches[0], Patch::SetText { path, text } if *path == vec![0, 0] && text == "2")
+            );
+        }
+
+        #[test]
+        /// Added, changed and removed attributes must each produce one patch.
+        fn test_diff_attributes() {
+            let old = tag(
+                "div",
+                vec![("id", "a"), ("class", "x"), ("title", "t")],
+                vec![],
+            );
+            let new = tag(
+                "div",
+                vec![("id", "b"), ("class", "x"), ("lang", "en")],
+                vec![],
+            );
+            let patches = diff(&old, &new);
+            assert_eq!(patches.len(), 3);
+            assert!(
+                matches!(&patches[0], Patch::SetAttribute { path, key, value } if path.is_empty() && key == "id" && value == "b")
+            );
+            assert!(
+                matches!(&patches[1], Patch::SetAttribute { key, value, .. } if key == "lang" && value == "en")
+            );
+            assert!(matches!(&patches[2], Patch::RemoveAttribute { key, .. } if key == "title"));
+        }
+
+        #[test]
+        /// Nodes of a different kind, or tags with a different name, must be replaced as a whole.
+        fn test_diff_replace() {
+            let old = tag("div", vec![], vec![tag("p", vec![], vec![]), text("a")]);
+            let new = tag(
+                "div",
+                vec![],
+                vec![tag("span", vec![], vec![]), tag("b", vec![], vec![])],
+            );
+            let patches = diff(&old, &new);
+            assert_eq!(patches.len(), 2);
+            assert!(
+                matches!(&patches[0], Patch::Replace { path, node } if *path == vec![0] && matches!(&node.node_type, NodeType::Tag(name) if name == "span"))
+            );
+            assert!(matches!(&patches[1], Patch::Replace { path, .. } if *path == vec![1]));
+        }
+
+        #[test]
+        /// Comments with the same content must be left alone, while a comment whose content changed
+        /// must be replaced, since there is no patch that changes a comment.
+        fn test_diff_comments() {
+            let comment = |content: &str| VirtualNode {
+                node_type: NodeType::Comment(content.to_owned()),
+                ..text("")
+            };
+            let old = tag("div", vec![], vec![comment("a"), comment("b")]);
+            let new = tag("div", vec![], vec![comment("a"), comment("c")]);
+            let patches = diff(&old, &new);
+            assert_eq!(patches.len(), 1);
+            assert!(
+                matches!(&patches[0], Patch::Replace { path, node } if *path == vec![1] && matches!(&node.node_type, NodeType::Comment(content) if content == "c"))
+            );
+        }
+
+        #[test]
+        /// Extra new children must be inserted in ascending order, after the common ones are diffed.
+        fn test_diff_insert() {
+            let old = tag("ul", vec![], vec![tag("li", vec![], vec![text("1")])]);
+            let new = tag(
+                "ul",
+                vec![],
+                vec![
+                    tag("li", vec![], vec![text("1")]),
+                    tag("li", vec![], vec![text("2")]),
+                    tag("li", vec![], vec![text("3")]),
+                ],
+            );
+            let patches = diff(&old, &new);
+            assert_eq!(patches.len(), 2);
+            assert!(
+                matches!(&patches[0], Patch::Insert { parent, index, .. } if parent.is_empty() && *index == 1)
+            );
+            assert!(matches!(&patches[1], Patch::Insert { index, .. } if *index == 2));
+        }
+
+        #[test]
+        /// Extra old children must be removed in descending order, so earlier removals don't shift the
+        /// indexes of later ones.
+        fn test_diff_remove() {
+            let old = tag(
+                "ul",
+                vec![],
+                vec![
+                    tag("li", vec![], vec![]),
+                    tag("li", vec![], vec![]),
+                    tag("li", vec![], vec![]),
+                ],
+            );
+            let new = tag("ul", vec![], vec![tag("li", vec![], vec![])]);
+            let patches = diff(&old, &new);
+            assert_eq!(patches.len(), 2);
+            assert!(matches!(&patches[0], Patch::Remove { index, .. } if *index == 2));
+            assert!(matches!(&patches[1], Patch::Remove { index, .. } if *index == 1));
+        }
+
+        #[test]
+        /// `diff_children` must treat the given lists as children of a node at the empty path.
+        fn test_diff_children() {
+            let old = vec![tag("p", vec![], vec![])];
+            let new: Vec<VirtualNode> = Vec::new();
+            let patches = diff_children(&old, &new);
+            assert_eq!(patches.len(), 1);
+            assert!(
+                matches!(&patches[0], Patch::Remove { parent, index } if parent.is_empty() && *index == 0)
+            );
+        }
+
+        fn keyed(name: &str, key: &str) -> VirtualNode {
+            tag(name, vec![("key", key)], vec![text(key)])
+        }
+
+        /// Applies `patches` to a list of nodes, following the same rules as the DOM does, so the
+        /// result can be compared with the new list. Only top level patches are supported.
+        fn apply(old: &Vec<VirtualNode>, patches: &Vec<Patch>) -> Vec<VirtualNode> {
+            let mut result = old.clone();
+            for patch in patches {
+                match patch {
+                    Patch::Insert {
+                        parent,
+                        index,
+                        node,
+                    } if parent.is_empty() => result.insert(*index, node.clone()),
+                    Patch::Remove { parent, index } if parent.is_empty() => {
+                        result.remove(*index);
+                    }
+                    Patch::Move { parent, from, to } if parent.is_empty() => {
+                        let moved = result.remove(*from);
+                        result.insert(*to, moved);
+                    }
+                    _ => {}
+                }
+            }
+            result
+        }
+
+        fn get_keys(nodes: &Vec<VirtualNode>) -> Vec<String> {
+            nodes
+                .iter()
+                .map(|node| node.attributes.get("key").unwrap().to_owned())
+                .collect()
+        }
+
+        #[test]
+        /// Reordered keyed children must be moved, not replaced, and the ones which kept their order
+        /// must not move at all.
+        fn test_diff_keyed_reorder() {
+            let old = vec![
+                keyed("li", "a"),
+                keyed("li", "b"),
+                keyed("li", "c"),
+                keyed("li", "d"),
+            ];
+            let new = vec![
+                keyed("li", "d"),
+                keyed("li", "a"),
+                keyed("li", "b"),
+                keyed("li", "c"),
+            ];
+            let patches = diff_children(&old, &new);
+            assert_eq!(patches.len(), 1);
+            assert!(matches!(&patches[0], Patch::Move { from, to, .. } if *from == 3 && *to == 0));
+            assert_eq!(get_keys(&apply(&old, &patches)), get_keys(&new));
+        }
+
+        #[test]
+        /// Keyed children must be inserted, removed and moved in a way that results in the new order.
+        fn test_diff_keyed_insert_remove_move() {
+            let old = vec![
+                keyed("li", "a"),
+                keyed("li", "b"),
+                keyed("li", "c"),
+                keyed("li", "d"),
+                keyed("li", "e"),
+            ];
+            let new = vec![
+                keyed("li", "e"),
+                keyed("li", "x"),
+                keyed("li", "c"),
+                keyed("li", "a"),
+                keyed("li", "y"),
+                keyed("li", "d"),
+            ];
+            let patches = diff_children(&old, &new);
+            assert!(!patches
+                .iter()
+                .any(|patch| matches!(patch, Patch::Replace { .. })));
+            assert_eq!(get_keys(&apply(&old, &patches)), get_keys(&new));
+        }
+
+        #[test]
+        /// A matched child must be diffed at the position it has after it was moved.
+        fn test_diff_keyed_nested_patch_path() {
+            let old = vec![keyed("li", "a"), keyed("li", "b")];
+            let new = vec![
+                tag("li", vec![("key", "b")], vec![text("changed")]),
+                keyed("li", "a"),
+            ];
+            let patches = diff_children(&old, &new);
+            let set_text = patches
+                .iter()
+                .find(|patch| matches!(patch, Patch::SetText { .. }));
+            assert!(
+                matches!(set_text, Some(Patch::SetText { path, text }) if *path == vec![0, 0] && text == "changed")
+            );
+        }
+
+        #[test]
+        /// The `key` attribute must never be patched, since it doesn't exist in the DOM.
+        fn test_diff_key_is_not_an_attribute() {
+            let old = vec![tag("li", vec![("key", "a"), ("id", "1")], vec![])];
+            let new = vec![tag("li", vec![("key", "a"), ("id", "2")], vec![])];
+            let patches = diff_children(&old, &new);
+            assert_eq!(patches.len(), 1);
+            assert!(matches!(&patches[0], Patch::SetAttribute { key, .. } if key == "id"));
+        }
+
+        #[test]
+        /// `get_longest_increasing_subsequence` must return the values of one of the longest strictly
+        /// increasing subsequences.
+        fn test_get_longest_increasing_subsequence() {
+            let result = get_longest_increasing_subsequence(&vec![3, 0, 1, 5, 2, 4]);
+            assert_eq!(result, HashSet::from([0, 1, 2, 4]));
+            assert!(get_longest_increasing_subsequence(&vec![]).is_empty());
+        }
+
+        #[test]
+        /// Patches must be serializable, so they can be logged or sent to devtools.
+        fn test_patch_serialize() {
+            let patch = Patch::SetText {
+                path: vec![0, 1],
+                text: "hi".to_owned(),
+            };
+            let serialized = serde_json::to_string(&patch).unwrap();
+            assert_eq!(serialized, "{\"SetText\":{\"path\":[0,1],\"text\":\"hi\"}}");
+        }
+    }
+}
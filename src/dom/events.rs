@@ -0,0 +1,241 @@
+/// Binds the expressions of event attributes, like `onclick={...}`, to real event listeners.
+pub mod events_mod {
+    use serde::{Deserialize, Serialize};
+    use serde_json::{from_str, to_string};
+    use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+    use web_sys::{js_sys::Function, Element, Event};
+
+    use crate::{
+        const_util::const_util_mod::{
+            EVENT_ATTRIBUTE_PREFIX, EVENT_PARAMETER, SET_STATE_PARAMETER,
+        },
+        dom::dom_mod::{get_mounted_component, get_mounted_state},
+        error::error_mod::{error_handler, Error},
+        evaluator::evaluator_mod::{
+            evaluate_expression_in_context, get_attribute_expression, Scope,
+        },
+    };
+
+    /// Everything an event handler needs in order to run later, when its event fires. This is what an
+    /// event attribute evaluates to; it is stored as a JSON string in the evaluated virtual node, so a
+    /// change in any part of it(e.g. a prop or a loop variable) causes the listener to be replaced.
+    /// State is not a part of it, as handlers always see the latest state of their component.
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    pub struct HandlerContext {
+        pub expression: String,
+        pub component_id: u32,
+        pub props: String,
+        pub bindings: Vec<(String, String)>, // loop variables and their values as JSON strings
+    }
+
+    /// An event listener which is attached to an element. `_set_state` is the function that handlers
+    /// can call to update the state of their component; it is never read, but it is kept here so it
+    /// lives exactly as long as the listener.
+    #[derive(Debug)]
+    pub struct Listener {
+        pub event: String,
+        pub handler: Closure<dyn FnMut(Event)>,
+        pub _set_state: Closure<dyn FnMut(Function)>,
+    }
+
+    /// Returns the name of the event which the given event attribute listens to, e.g. `click` for
+    /// `onclick` or `onClick`.
+    pub fn get_event_name(attribute: &str) -> String {
+        attribute[EVENT_ATTRIBUTE_PREFIX.len()..].to_lowercase()
+    }
+
+    /// Given the value of an event attribute, the component it was used in and the loop variables
+    /// around it, returns its `HandlerContext` as a JSON string. The expression itself is not evaluated
+    /// here; it runs every time the event fires.
+    pub fn get_handler_context_string(
+        value: &String,
+        component_id: u32,
+        props: &String,
+        scope: &Scope,
+    ) -> Result<String, Error> {
+        let expression_result = get_attribute_expression(value);
+        if expression_result.is_err() {
+            return Err(expression_result.unwrap_err());
+        }
+        let bindings_result = scope.get_stringified_bindings();
+        if bindings_result.is_err() {
+            let name = bindings_result.unwrap_err();
+            return Err(Error::TypeError(format!(
+                "The loop variable `{name}` couldn't be converted to JSON, so it can't be used in an event handler."
+            )));
+        }
+        let context = HandlerContext {
+            expression: expression_result.unwrap(),
+            component_id,
+            props: props.to_owned(),
+            bindings: bindings_result.unwrap(),
+        };
+        let context_string_result = to_string(&context);
+        if context_string_result.is_err() {
+            return Err(Error::TypeError(
+                "Failed to stringify the context of an event handler.".to_owned(),
+            ));
+        }
+        Ok(context_string_result.unwrap())
+    }
+
+    /// Parses a JSON string which was created by `get_handler_context_string`.
+    fn parse_handler_context(context: &str) -> Result<HandlerContext, Error> {
+        let context_result: Result<HandlerContext, serde_json::Error> = from_str(context);
+        if context_result.is_err() {
+            return Err(Error::_InvestigationNeeded(format!(
+                "The following event handler context was malformed: {context}"
+            )));
+        }
+        Ok(context_result.unwrap())
+    }
+
+    /// Runs an event handler: its expression is evaluated with the latest state of its component, its
+    /// props, its loop variables, `event` and `set_state` in scope. If the expression evaluates to a
+    /// function, e.g. `onclick={(e) => ...}`, that function is called with the event as well.
+    fn run_handler(
+        context: &HandlerContext,
+        set_state: &JsValue,
+        event: Event,
+    ) -> Result<(), Error> {
+        let state = get_mounted_state(context.component_id);
+        if state.is_none() {
+            return Ok(()); // the component has been unmounted in the meantime.
+        }
+        let mut scope = Scope::from_stringified_bindings(&context.bindings);
+        let event_value: JsValue = event.into();
+        scope = scope
+            .with_binding(EVENT_PARAMETER, event_value.clone())
+            .with_binding(SET_STATE_PARAMETER, set_state.clone());
+        let evaluation_result = evaluate_expression_in_context(
+            &context.expression,
+            &state.unwrap(),
+            &context.props,
+            &scope,
+        );
+        if evaluation_result.is_err() {
+            return Err(evaluation_result.unwrap_err());
+        }
+        let evaluated_value = evaluation_result.unwrap();
+        if !evaluated_value.is_function() {
+            return Ok(());
+        }
+        let callback: Function = evaluated_value.unchecked_into();
+        let call_result = callback.call1(&JsValue::undefined(), &event_value);
+        if call_result.is_err() {
+            let msg = format!("{:?}", call_result.unwrap_err());
+            return Err(Error::EvaluationError(format!(
+                "An event handler threw the following error: {msg}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Creates a listener for the given event attribute and attaches it to `element`. Returns an
+    /// `Err` variant if `context` is malformed or the DOM refuses the listener.
+    pub fn attach_listener(
+        element: &Element,
+        attribute: &str,
+        context: &str,
+    ) -> Result<Listener, Error> {
+        let context_result = parse_handler_context(context);
+        if context_result.is_err() {
+            return Err(context_result.unwrap_err());
+        }
+        let context = context_result.unwrap();
+        let component_id = context.component_id;
+        let set_state = Closure::<dyn FnMut(Function)>::new(move |callback: Function| {
+            let component = get_mounted_component(component_id);
+            if component.is_some() {
+                component.unwrap().set_state(callback);
+            }
+        });
+        let set_state_function: JsValue = set_state.as_ref().clone();
+        let handler = Closure::<dyn FnMut(Event)>::new(move |event: Event| {
+            let run_result = run_handler(&context, &set_state_function, event);
+            if run_result.is_err() {
+                error_handler(run_result.unwrap_err());
+            }
+        });
+        let event = get_event_name(attribute);
+        let add_result =
+            element.add_event_listener_with_callback(&event, handler.as_ref().unchecked_ref());
+        if add_result.is_err() {
+            return Err(Error::DomError(add_result.unwrap_err()));
+        }
+        Ok(Listener {
+            event,
+            handler,
+            _set_state: set_state,
+        })
+    }
+
+    /// Detaches `listener` from `element`. The listener is dropped afterwards, which releases its
+    /// closures.
+    pub fn detach_listener(element: &Element, listener: Listener) -> Result<(), Error> {
+        let remove_result = element.remove_event_listener_with_callback(
+            &listener.event,
+            listener.handler.as_ref().unchecked_ref(),
+        );
+        if remove_result.is_err() {
+            return Err(Error::DomError(remove_result.unwrap_err()));
+        }
+        Ok(())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::const_util::const_util_mod::is_input_event_attribute;
+
+        #[test]
+        /// `get_event_name` must strip the `on` prefix and ignore the case of the attribute.
+        fn test_get_event_name() {
+            assert_eq!(get_event_name("onclick"), "click");
+            assert_eq!(get_event_name("onKeyDown"), "keydown");
+        }
+
+        #[test]
+        /// Only `on` followed by the name of an event is an event attribute, whatever its case.
+        fn test_is_input_event_attribute() {
+            for attribute in ["onclick", "onClick", "oninput", "onKeyDown", "onsubmit"] {
+                assert!(is_input_event_attribute(attribute), "{attribute}");
+            }
+            for attribute in [
+                "on",
+                "one",
+                "online",
+                "only",
+                "onion",
+                "click",
+                "data-onclick",
+            ] {
+                assert!(!is_input_event_attribute(attribute), "{attribute}");
+            }
+        }
+
+        #[test]
+        /// A `HandlerContext` must survive being stored as a JSON string.
+        fn test_parse_handler_context() {
+            let context = HandlerContext {
+                expression: "() => set_state(s => ({...s, done: !item.done}))".to_owned(),
+                component_id: 3,
+                props: "{\"title\":\"hi\"}".to_owned(),
+                bindings: vec![("item".to_owned(), "{\"done\":false}".to_owned())],
+            };
+            let context_string = to_string(&context).unwrap();
+            let parsed_context_result = parse_handler_context(&context_string);
+            assert!(matches!(parsed_context_result, Ok(parsed) if parsed == context));
+        }
+
+        #[test]
+        /// `parse_handler_context` must fail on strings which weren't created from a `HandlerContext`.
+        fn test_parse_handler_context_invalid() {
+            let parsed_context_result = parse_handler_context("{state.count}");
+            assert!(matches!(
+                parsed_context_result,
+                Err(Error::_InvestigationNeeded(_))
+            ));
+        }
+    }
+}
@@ -1,19 +1,36 @@
+mod events;
+mod mounted;
+
 pub mod dom_mod {
-    use std::collections::HashMap;
+    use std::collections::{HashMap, HashSet};
 
-    use serde_wasm_bindgen::to_value;
-    use wasm_bindgen::JsValue;
-    use web_sys::{console::log_1, window, Document, Element, Text, Window};
+    use wasm_bindgen::{JsCast, JsValue};
+    use web_sys::{js_sys::JSON, window, Document, Element, Node, Text, Window};
 
+    use super::events::events_mod::{
+        attach_listener, detach_listener, get_event_name, get_handler_context_string, Listener,
+    };
+    use super::mounted::mounted_mod::{
+        dom_nodes, instances_of, register_instance, restore_effect_runs, restore_instance,
+        take_effect_runs, take_instance, with_instance, MountedComponent, RenderedNode,
+    };
     use crate::{
-        component::component_mod::{effects_runner, Component, Effects},
+        component::component_mod::{
+            effects_runner, get_props_from_attributes, get_props_with_prop, Component, Effects,
+        },
         const_util::const_util_mod::{
-            is_input_true_literal, APP_WRAPPER_ID, RENDER_ELSE_ATTRIBUTE_NAME,
-            RENDER_ELSE_IF_ATTRIBUTE_NAME, RENDER_IF_ATTRIBUTE_NAME,
+            is_input_event_attribute, is_input_key_attribute, is_input_render_attribute,
+            is_input_true_literal, APP_WRAPPER_ID, DEFAULT_SLOT_NAME, FOREIGN_OBJECT_TAG_NAME,
+            KEY_ATTRIBUTE_NAME, MATHML_NAMESPACE, MATH_TAG_NAME, RENDER_ELSE_ATTRIBUTE_NAME,
+            RENDER_ELSE_IF_ATTRIBUTE_NAME, RENDER_FOR_ATTRIBUTE_NAME, RENDER_IF_ATTRIBUTE_NAME,
+            SLOT_ATTRIBUTE_NAME, SLOT_NAME_ATTRIBUTE_NAME, SLOT_SCOPE_ATTRIBUTE_NAME,
+            SLOT_TAG_NAME, SVG_NAMESPACE, SVG_TAG_NAME,
         },
-        error::error_mod::Error,
+        diff::diff_mod::{diff_children, Patch},
+        error::error_mod::{locate_error, warning_handler, Error, Warning},
         evaluator::evaluator_mod::{
-            evaluate_attribute_value_to_raw_string, evaluate_text_value_to_raw_string,
+            evaluate_attribute_value_to_json, evaluate_attribute_value_to_raw_string,
+            evaluate_loop, evaluate_text_value_to_raw_string, validate_slot_scope, Scope,
         },
         parser::parser_mod::{NodeType, VirtualNode},
     };
@@ -83,43 +100,219 @@ pub mod dom_mod {
         Ok(app_wrapper)
     }
 
-    /// Adds attributes to the provided element. Note that attribute values are evaluated. Returns an
-    /// `Err` if an error occurs during evaluation or setting attributes.
-    fn add_attributes(
+    /// Points `error` at the attribute of `node` named `attribute`, or at `node` itself if it has no
+    /// such attribute, in the presenter of `current_component`; which is the component `node` was
+    /// used in.
+    fn locate_node_error(
+        error: Error,
+        node: &VirtualNode,
+        attribute: Option<&str>,
         current_component: &Component,
-        attributes: &HashMap<String, String>,
-        element: &Element,
-    ) -> Result<(), Error> {
-        for (key, value) in attributes {
+    ) -> Error {
+        let span = attribute
+            .and_then(|attribute| node.attribute_spans.get(attribute))
+            .unwrap_or(&node.span);
+        locate_error(error, span, &current_component.presenter())
+    }
+
+    /// Evaluates attribute values in the context of the provided component and scope. `render-*`
+    /// attributes are left out, as they only decide whether or how many times a node is rendered and
+    /// have no meaning in the DOM. Event attributes are not evaluated; their value becomes the context
+    /// which their handler needs to run later.
+    /// Returns an `Err` if an error occurs during evaluation.
+    fn evaluate_attributes(
+        current_root: &VirtualNode,
+        current_component: &Component,
+        scope: &Scope,
+    ) -> Result<HashMap<String, String>, Error> {
+        let mut evaluated_attributes: HashMap<String, String> = HashMap::new();
+        for (key, value) in &current_root.attributes {
+            if is_input_render_attribute(key) {
+                continue;
+            }
+            if is_input_event_attribute(key) {
+                let context_result = get_handler_context_string(
+                    value,
+                    current_component.get_id(),
+                    current_component.get_props(),
+                    scope,
+                );
+                if context_result.is_err() {
+                    return Err(locate_node_error(
+                        context_result.unwrap_err(),
+                        current_root,
+                        Some(key),
+                        current_component,
+                    ));
+                }
+                evaluated_attributes.insert(key.to_owned(), context_result.unwrap());
+                continue;
+            }
             let attr_value_result =
-                evaluate_attribute_value_to_raw_string(value.to_owned(), current_component);
+                evaluate_attribute_value_to_raw_string(value.to_owned(), current_component, scope);
             if attr_value_result.is_err() {
-                return Err(attr_value_result.unwrap_err());
+                return Err(locate_node_error(
+                    attr_value_result.unwrap_err(),
+                    current_root,
+                    Some(key),
+                    current_component,
+                ));
             }
-            let attr_value = attr_value_result.unwrap();
-            let set_attribute_result = element.set_attribute(key, &attr_value);
-            if set_attribute_result.is_err() {
-                return Err(Error::DomError(set_attribute_result.unwrap_err()));
+            evaluated_attributes.insert(key.to_owned(), attr_value_result.unwrap());
+        }
+        Ok(evaluated_attributes)
+    }
+
+    /// Evaluates the attributes of a component tag in the context of the component which it was used
+    /// in, i.e. the parent. Each value is stored as a JSON string, so it can later become a prop of the
+    /// child without losing its type; `key` is the exception, which is evaluated like any other
+    /// attribute since it never becomes a prop. `render-*` attributes are left out.
+    /// Returns an `Err` if an error occurs during evaluation.
+    fn evaluate_props(
+        current_root: &VirtualNode,
+        current_component: &Component,
+        scope: &Scope,
+    ) -> Result<HashMap<String, String>, Error> {
+        let mut evaluated_props: HashMap<String, String> = HashMap::new();
+        for (key, value) in &current_root.attributes {
+            if is_input_render_attribute(key) {
+                continue;
+            }
+            if is_input_key_attribute(key) {
+                let key_result = evaluate_attribute_value_to_raw_string(
+                    value.to_owned(),
+                    current_component,
+                    scope,
+                );
+                if key_result.is_err() {
+                    return Err(locate_node_error(
+                        key_result.unwrap_err(),
+                        current_root,
+                        Some(key),
+                        current_component,
+                    ));
+                }
+                evaluated_props.insert(key.to_owned(), key_result.unwrap());
+                continue;
+            }
+            let prop_result =
+                evaluate_attribute_value_to_json(value.to_owned(), current_component, scope);
+            if prop_result.is_err() {
+                return Err(locate_node_error(
+                    prop_result.unwrap_err(),
+                    current_root,
+                    Some(key),
+                    current_component,
+                ));
+            }
+            let prop = prop_result.unwrap();
+            if prop.is_some() {
+                evaluated_props.insert(key.to_owned(), prop.unwrap());
             }
         }
-        Ok(())
+        Ok(evaluated_props)
     }
 
-    /// Recursively calls the `self::construct_dom` function on each child of the current virtual node;
-    /// This is when a child doesn't have a `render-*` special attribute. If it has one of those attributes,
-    /// It will be added to the DOM only if its evaluated result is true according to how `if-else` expressions
-    /// are evaluated.
-    /// Returns an `Err` variant if an `Err` variant is returned from any of the calls to `self::construct_dom`.
-    fn add_children(
-        children: &Vec<VirtualNode>,
-        current_component: &mut Component,
-        element: &Element,
-        document: &Document,
+    /// Evaluates a child which has a `render-for` attribute once per element of the iterated array,
+    /// each time in a scope which has the loop variables bound, and adds the results to
+    /// `evaluated_children`. A `render-if` on the same node is evaluated per element, so it can
+    /// refer to the loop variables; `render-else-if` and `render-else` can't follow it.
+    /// Repeated nodes are expected to have a unique `key`, so they can be matched when the array is
+    /// reordered; a warning is logged if they don't.
+    /// Returns an `Err` variant if evaluating the loop or any of the repeated nodes fails.
+    fn evaluate_repeated_child(
+        child: &VirtualNode,
+        loop_value: &String,
+        current_component: &Component,
+        scope: &Scope,
+        evaluated_children: &mut Vec<VirtualNode>,
     ) -> Result<(), Error> {
+        let item_scopes_result = evaluate_loop(loop_value, current_component, scope);
+        if item_scopes_result.is_err() {
+            return Err(locate_node_error(
+                item_scopes_result.unwrap_err(),
+                child,
+                Some(RENDER_FOR_ATTRIBUTE_NAME),
+                current_component,
+            ));
+        }
+        let item_scopes = item_scopes_result.unwrap();
+        if !item_scopes.is_empty() && !child.attributes.contains_key(KEY_ATTRIBUTE_NAME) {
+            warning_handler(Warning::MissingKey(format!(
+                "Nodes which are repeated by `{RENDER_FOR_ATTRIBUTE_NAME}={loop_value}` should have a `{KEY_ATTRIBUTE_NAME}` attribute; otherwise they are matched by their position when the list changes."
+            )));
+        }
+        let mut seen_keys: HashSet<String> = HashSet::new();
+        for item_scope in item_scopes {
+            let render_node_result: Result<(bool, IfExprState), Error> = should_node_render(
+                child,
+                IfExprState::NotReached,
+                current_component,
+                &item_scope,
+            );
+            if render_node_result.is_err() {
+                return Err(render_node_result.unwrap_err());
+            }
+            if !render_node_result.unwrap().0 {
+                continue;
+            }
+            let evaluated_child_result = evaluate_node(child, current_component, &item_scope);
+            if evaluated_child_result.is_err() {
+                return Err(evaluated_child_result.unwrap_err());
+            }
+            let evaluated_child = evaluated_child_result.unwrap();
+            let key = evaluated_child.attributes.get(KEY_ATTRIBUTE_NAME);
+            if key.is_some() && !seen_keys.insert(key.unwrap().to_owned()) {
+                warning_handler(Warning::DuplicateKey(format!(
+                    "More than one node which is repeated by `{RENDER_FOR_ATTRIBUTE_NAME}={loop_value}` has the key `{}`; keys must be unique among siblings.",
+                    key.unwrap()
+                )));
+            }
+            push_evaluated_node(evaluated_children, evaluated_child);
+        }
+        Ok(())
+    }
+
+    /// Adds an evaluated node to `evaluated_children`; or its children, if it is a fragment. Fragments
+    /// only group their children in the presenter, so they never reach the DOM.
+    fn push_evaluated_node(evaluated_children: &mut Vec<VirtualNode>, node: VirtualNode) {
+        if matches!(node.node_type, NodeType::Fragment) {
+            evaluated_children.extend(node.children);
+        } else {
+            evaluated_children.push(node);
+        }
+    }
+
+    /// Evaluates each child of the current virtual node. A child which has a `render-*` special
+    /// attribute is only kept if its evaluated result is true according to how `if-else` expressions
+    /// are evaluated, and a child which has a `render-for` attribute is repeated once per element of
+    /// the iterated array.
+    /// Returns an `Err` variant if evaluating any of the children fails.
+    fn evaluate_children(
+        children: &[VirtualNode],
+        current_component: &Component,
+        scope: &Scope,
+    ) -> Result<Vec<VirtualNode>, Error> {
+        let mut evaluated_children: Vec<VirtualNode> = Vec::new();
         let mut if_state_expr: IfExprState = IfExprState::NotReached;
         for child in children {
+            let loop_value = child.attributes.get(RENDER_FOR_ATTRIBUTE_NAME);
+            if loop_value.is_some() {
+                if_state_expr = IfExprState::NotReached;
+                let repeat_result = evaluate_repeated_child(
+                    child,
+                    loop_value.unwrap(),
+                    current_component,
+                    scope,
+                    &mut evaluated_children,
+                );
+                if repeat_result.is_err() {
+                    return Err(repeat_result.unwrap_err());
+                }
+                continue;
+            }
             let render_node_result: Result<(bool, IfExprState), Error> =
-                should_node_render(child.clone(), if_state_expr, current_component);
+                should_node_render(child, if_state_expr, current_component, scope);
             if render_node_result.is_err() {
                 return Err(render_node_result.unwrap_err());
             }
@@ -128,78 +321,317 @@ pub mod dom_mod {
             if !should_add {
                 continue;
             }
-            let node_type = child.node_type.clone();
-            let construct_result;
-            match node_type {
-                NodeType::Component(mut component) => {
-                    construct_result =
-                        self::construct_dom(child.clone(), &mut component, &element, &document);
+            let evaluated_child_result = evaluate_node(child, current_component, scope);
+            if evaluated_child_result.is_err() {
+                return Err(evaluated_child_result.unwrap_err());
+            }
+            push_evaluated_node(&mut evaluated_children, evaluated_child_result.unwrap());
+        }
+        Ok(evaluated_children)
+    }
+
+    /// Evaluates the children which are passed to a component in the context of the current component.
+    /// Children with a `slot-scope` attribute are content for a scoped slot, which can only be
+    /// evaluated once the slot provides its values; so they are kept as `ScopedSlot` nodes, along
+    /// with what they need from the current context. Their `slot` and `slot-scope` attributes are
+    /// evaluated right away though.
+    fn evaluate_passed_children(
+        children: &[VirtualNode],
+        current_component: &Component,
+        scope: &Scope,
+    ) -> Result<Vec<VirtualNode>, Error> {
+        let (scoped_children, children): (Vec<VirtualNode>, Vec<VirtualNode>) = children
+            .iter()
+            .cloned()
+            .partition(|child| child.attributes.contains_key(SLOT_SCOPE_ATTRIBUTE_NAME));
+        let evaluated_children_result = evaluate_children(&children, current_component, scope);
+        if evaluated_children_result.is_err() || scoped_children.is_empty() {
+            return evaluated_children_result;
+        }
+        let mut evaluated_children = evaluated_children_result.unwrap();
+        let bindings_result = scope.get_stringified_bindings();
+        if bindings_result.is_err() {
+            let name = bindings_result.unwrap_err();
+            return Err(Error::TypeError(format!(
+                "The loop variable `{name}` couldn't be converted to JSON, so it can't be used in the content of a scoped slot."
+            )));
+        }
+        let bindings = bindings_result.unwrap();
+        for mut child in scoped_children {
+            let mut attributes: HashMap<String, String> = HashMap::new();
+            for key in [SLOT_ATTRIBUTE_NAME, SLOT_SCOPE_ATTRIBUTE_NAME] {
+                let value = child.attributes.get(key);
+                if value.is_none() {
+                    continue;
                 }
-                _ => {
-                    construct_result =
-                        self::construct_dom(child.clone(), current_component, &element, &document);
+                let mut evaluated_value_result = evaluate_attribute_value_to_raw_string(
+                    value.unwrap().to_owned(),
+                    current_component,
+                    scope,
+                );
+                if key == SLOT_SCOPE_ATTRIBUTE_NAME && evaluated_value_result.is_ok() {
+                    let validation_result =
+                        validate_slot_scope(evaluated_value_result.as_ref().unwrap());
+                    if validation_result.is_err() {
+                        evaluated_value_result = Err(validation_result.unwrap_err());
+                    }
                 }
+                if evaluated_value_result.is_err() {
+                    return Err(locate_node_error(
+                        evaluated_value_result.unwrap_err(),
+                        &child,
+                        Some(key),
+                        current_component,
+                    ));
+                }
+                attributes.insert(key.to_owned(), evaluated_value_result.unwrap());
             }
-            if construct_result.is_err() {
-                return Err(construct_result.unwrap_err());
-            }
+            child.attributes.remove(SLOT_ATTRIBUTE_NAME);
+            child.attributes.remove(SLOT_SCOPE_ATTRIBUTE_NAME);
+            evaluated_children.push(VirtualNode {
+                node_type: NodeType::ScopedSlot(current_component.clone(), bindings.clone()),
+                attributes,
+                span: child.span,
+                children: vec![child],
+                attribute_spans: HashMap::new(),
+            });
         }
-        Ok(())
+        Ok(evaluated_children)
     }
 
-    /// Constructs a tag element from the given virtual node and appends it to the provided parent.
-    /// Returns an `Err` variant which explains what went wrong, `Ok` otherwise.
-    fn construct_tag(
-        current_root: VirtualNode,
-        current_component: &mut Component,
-        parent: &Element,
-        document: &Document,
-        tag_name: String,
-    ) -> Result<(), Error> {
-        let new_element_result = document.create_element(&tag_name);
-        if new_element_result.is_err() {
-            return Err(Error::DomError(new_element_result.unwrap_err()));
+    /// Returns the name of the slot which the given child of a component was passed to. Only elements
+    /// and scoped slot content can have a `slot` attribute; everything else goes to the default slot.
+    fn get_passed_slot_name(node: &VirtualNode) -> &str {
+        match node.node_type {
+            NodeType::Tag(_) | NodeType::ScopedSlot(..) => node
+                .attributes
+                .get(SLOT_ATTRIBUTE_NAME)
+                .map(|name| name.as_str())
+                .unwrap_or(DEFAULT_SLOT_NAME),
+            _ => DEFAULT_SLOT_NAME,
         }
-        let new_element = new_element_result.unwrap();
+    }
 
-        let attributes = &current_root.attributes;
-        let add_attributes_result = add_attributes(current_component, attributes, &new_element);
-        if add_attributes_result.is_err() {
-            return Err(add_attributes_result.unwrap_err());
+    /// Evaluates the attributes of a `<slot>`, other than its `name`, to the object which is bound to
+    /// the `slot-scope` of the content passed to it.
+    fn evaluate_slot_scope(
+        current_root: &VirtualNode,
+        current_component: &Component,
+        scope: &Scope,
+    ) -> Result<JsValue, Error> {
+        let mut slot = VirtualNode {
+            node_type: current_root.node_type.clone(),
+            attributes: current_root.attributes.clone(),
+            children: Vec::new(),
+            span: current_root.span,
+            attribute_spans: current_root.attribute_spans.clone(),
+        };
+        slot.attributes.remove(SLOT_NAME_ATTRIBUTE_NAME);
+        let props_result = evaluate_props(&slot, current_component, scope);
+        if props_result.is_err() {
+            return Err(props_result.unwrap_err());
         }
-
-        let append_child_result = parent.append_child(&new_element);
-        if append_child_result.is_err() {
-            return Err(Error::DomError(append_child_result.unwrap_err()));
+        let slot_scope_result = get_props_from_attributes(&props_result.unwrap());
+        if slot_scope_result.is_err() {
+            return Err(slot_scope_result.unwrap_err());
         }
-
-        let children = &current_root.children;
-        return add_children(children, current_component, &new_element, document);
+        let parsed_result = JSON::parse(&slot_scope_result.unwrap());
+        if parsed_result.is_err() {
+            return Err(Error::_InvestigationNeeded(
+                "The values of a scoped slot were not a valid JSON string.".to_owned(),
+            ));
+        }
+        Ok(parsed_result.unwrap())
     }
 
-    /// Crates a text node and appends it to the provided parent.
-    /// Returns an `Err` variant which explains what went wrong, `Ok` otherwise.
-    fn construct_text(
-        text: &String,
-        parent: &Element,
+    /// Evaluates a `<slot>` of the current component to a fragment holding the children passed to the
+    /// component whose `slot` attribute matches its `name`; or those without a `slot` attribute, if it
+    /// has no name. Content for a scoped slot is evaluated here, in the context of the component which
+    /// passed it, with the values of the slot bound to the name given by its `slot-scope`. The
+    /// children of `<slot>` itself are a fallback, which is only rendered if nothing was passed to it.
+    fn evaluate_slot(
+        current_root: &VirtualNode,
         current_component: &Component,
-    ) -> Result<(), Error> {
-        let evaluated_text_result = evaluate_text_value_to_raw_string(text, current_component);
-        if evaluated_text_result.is_err() {
-            return Err(evaluated_text_result.unwrap_err());
+        scope: &Scope,
+    ) -> Result<VirtualNode, Error> {
+        let mut name = DEFAULT_SLOT_NAME.to_owned();
+        let name_value = current_root.attributes.get(SLOT_NAME_ATTRIBUTE_NAME);
+        if name_value.is_some() {
+            let name_result = evaluate_attribute_value_to_raw_string(
+                name_value.unwrap().to_owned(),
+                current_component,
+                scope,
+            );
+            if name_result.is_err() {
+                return Err(locate_node_error(
+                    name_result.unwrap_err(),
+                    current_root,
+                    Some(SLOT_NAME_ATTRIBUTE_NAME),
+                    current_component,
+                ));
+            }
+            name = name_result.unwrap();
         }
-        let text = evaluated_text_result.unwrap();
-        let text_element_result = Text::new_with_data(&text);
-        if text_element_result.is_err() {
-            return Err(Error::DomError(text_element_result.unwrap_err()));
+        let passed_children: Vec<&VirtualNode> = scope
+            .get_slot()
+            .iter()
+            .filter(|child| get_passed_slot_name(child) == name)
+            .collect();
+        if passed_children.is_empty() {
+            let fallback_result =
+                evaluate_children(&current_root.children, current_component, scope);
+            if fallback_result.is_err() {
+                return Err(fallback_result.unwrap_err());
+            }
+            return Ok(VirtualNode {
+                node_type: NodeType::Fragment,
+                attributes: HashMap::new(),
+                children: fallback_result.unwrap(),
+                span: current_root.span,
+                attribute_spans: HashMap::new(),
+            });
         }
-        let text_element = text_element_result.unwrap();
 
-        let append_text_result = parent.append_child(&text_element);
-        if append_text_result.is_err() {
-            return Err(Error::DomError(append_text_result.unwrap_err()));
+        let mut children: Vec<VirtualNode> = Vec::new();
+        let mut slot_scope: Option<JsValue> = None;
+        for child in passed_children {
+            match &child.node_type {
+                NodeType::ScopedSlot(owner, bindings) => {
+                    if slot_scope.is_none() {
+                        let slot_scope_result =
+                            evaluate_slot_scope(current_root, current_component, scope);
+                        if slot_scope_result.is_err() {
+                            return Err(slot_scope_result.unwrap_err());
+                        }
+                        slot_scope = Some(slot_scope_result.unwrap());
+                    }
+                    let mut content_scope = Scope::from_stringified_bindings(bindings);
+                    let scope_name = child.attributes.get(SLOT_SCOPE_ATTRIBUTE_NAME);
+                    if scope_name.is_some() {
+                        content_scope = content_scope
+                            .with_binding(scope_name.unwrap(), slot_scope.clone().unwrap());
+                    }
+                    let content_result = evaluate_children(&child.children, owner, &content_scope);
+                    if content_result.is_err() {
+                        return Err(content_result.unwrap_err());
+                    }
+                    children.extend(content_result.unwrap());
+                }
+                NodeType::Tag(_) => {
+                    let mut child = child.clone();
+                    child.attributes.remove(SLOT_ATTRIBUTE_NAME);
+                    children.push(child);
+                }
+                _ => children.push(child.clone()),
+            }
+        }
+        Ok(VirtualNode {
+            node_type: NodeType::Fragment,
+            attributes: HashMap::new(),
+            children,
+            span: current_root.span,
+            attribute_spans: HashMap::new(),
+        })
+    }
+
+    /// Evaluates every expression in the given virtual node and its subtree in the context of the
+    /// provided component and scope. The result is a virtual node which can be turned into DOM nodes
+    /// as is. `Component` nodes are kept as they are; their subtree is evaluated once they are mounted,
+    /// in their own context. The children of a `Component` node are evaluated here though, since they
+    /// belong to the current component; they are rendered by the `<slot>`s of the mounted component.
+    fn evaluate_node(
+        current_root: &VirtualNode,
+        current_component: &Component,
+        scope: &Scope,
+    ) -> Result<VirtualNode, Error> {
+        match &current_root.node_type {
+            NodeType::Tag(tag_name) if tag_name == SLOT_TAG_NAME => {
+                evaluate_slot(current_root, current_component, scope)
+            }
+            NodeType::Tag(_) => {
+                let attributes_result = evaluate_attributes(current_root, current_component, scope);
+                if attributes_result.is_err() {
+                    return Err(attributes_result.unwrap_err());
+                }
+                let children_result =
+                    evaluate_children(&current_root.children, current_component, scope);
+                if children_result.is_err() {
+                    return Err(children_result.unwrap_err());
+                }
+                Ok(VirtualNode {
+                    node_type: current_root.node_type.clone(),
+                    attributes: attributes_result.unwrap(),
+                    children: children_result.unwrap(),
+                    span: current_root.span,
+                    attribute_spans: current_root.attribute_spans.clone(),
+                })
+            }
+            NodeType::Text(text) => {
+                let evaluated_text_result =
+                    evaluate_text_value_to_raw_string(text, current_component, scope);
+                if evaluated_text_result.is_err() {
+                    return Err(locate_node_error(
+                        evaluated_text_result.unwrap_err(),
+                        current_root,
+                        None,
+                        current_component,
+                    ));
+                }
+                Ok(VirtualNode {
+                    node_type: NodeType::Text(evaluated_text_result.unwrap()),
+                    attributes: HashMap::new(),
+                    children: Vec::new(),
+                    span: current_root.span,
+                    attribute_spans: HashMap::new(),
+                })
+            }
+            NodeType::Comment(_) | NodeType::ScopedSlot(..) => Ok(current_root.clone()),
+            NodeType::Fragment => {
+                let children_result =
+                    evaluate_children(&current_root.children, current_component, scope);
+                if children_result.is_err() {
+                    return Err(children_result.unwrap_err());
+                }
+                Ok(VirtualNode {
+                    node_type: NodeType::Fragment,
+                    attributes: HashMap::new(),
+                    children: children_result.unwrap(),
+                    span: current_root.span,
+                    attribute_spans: HashMap::new(),
+                })
+            }
+            NodeType::Component(_) => {
+                let props_result = evaluate_props(current_root, current_component, scope);
+                if props_result.is_err() {
+                    return Err(props_result.unwrap_err());
+                }
+                let children_result =
+                    evaluate_passed_children(&current_root.children, current_component, scope);
+                if children_result.is_err() {
+                    return Err(children_result.unwrap_err());
+                }
+                Ok(VirtualNode {
+                    node_type: current_root.node_type.clone(),
+                    attributes: props_result.unwrap(),
+                    children: children_result.unwrap(),
+                    span: current_root.span,
+                    attribute_spans: current_root.attribute_spans.clone(),
+                })
+            }
         }
-        Ok(())
+    }
+
+    /// Evaluates the vdom of a component in its own context, given the evaluated children which were
+    /// passed to it. The root node is treated like any other child, so the result is empty if it has a
+    /// `render-if` attribute which evaluates to false, and has one node per element if it has a
+    /// `render-for` attribute.
+    fn evaluate_component_vdom(
+        component: &Component,
+        slot: &[VirtualNode],
+    ) -> Result<Vec<VirtualNode>, Error> {
+        let vdom = component.get_vdom();
+        let scope = Scope::new().with_slot(slot.to_vec());
+        evaluate_children(std::slice::from_ref(vdom), component, &scope)
     }
 
     /// Given a node, the context of the component which it was used in and the previous state of
@@ -207,9 +639,10 @@ pub mod dom_mod {
     /// it returns an `Ok` variant which contains a tuple indicating if the node should be rendered, and
     /// the next state of `render-*` in the scope.
     fn should_node_render(
-        current_root: VirtualNode,
+        current_root: &VirtualNode,
         if_state_expr: IfExprState,
         current_component: &Component,
+        scope: &Scope,
     ) -> Result<(bool, IfExprState), Error> {
         let attrs = &current_root.attributes;
 
@@ -219,8 +652,11 @@ pub mod dom_mod {
 
         if if_.is_some() {
             let if_value = if_.unwrap();
-            let evaluated_if_value_result =
-                evaluate_attribute_value_to_raw_string(if_value.to_owned(), current_component);
+            let evaluated_if_value_result = evaluate_attribute_value_to_raw_string(
+                if_value.to_owned(),
+                current_component,
+                scope,
+            );
             if evaluated_if_value_result.is_ok() {
                 let evaluated_if_value: String = evaluated_if_value_result.unwrap();
                 let res = is_input_true_literal(&evaluated_if_value);
@@ -232,7 +668,12 @@ pub mod dom_mod {
                 }
                 return Ok((res, new_state));
             } else {
-                return Err(evaluated_if_value_result.unwrap_err());
+                return Err(locate_node_error(
+                    evaluated_if_value_result.unwrap_err(),
+                    current_root,
+                    Some(RENDER_IF_ATTRIBUTE_NAME),
+                    current_component,
+                ));
             }
         } else if else_if.is_some() {
             match if_state_expr {
@@ -241,14 +682,22 @@ pub mod dom_mod {
                     return Ok((false, if_state_expr));
                 }
                 IfExprState::NotReached => {
-                    return Err(Error::ParsingError(format!(
-                        "Didn't expect a `{RENDER_ELSE_IF_ATTRIBUTE_NAME}` attribute."
-                    )));
+                    return Err(locate_node_error(
+                        Error::ParsingError(format!(
+                            "Didn't expect a `{RENDER_ELSE_IF_ATTRIBUTE_NAME}` attribute."
+                        )),
+                        current_root,
+                        Some(RENDER_ELSE_IF_ATTRIBUTE_NAME),
+                        current_component,
+                    ));
                 }
             }
             let else_if_value = else_if.unwrap();
-            let evaluated_else_if_value_result =
-                evaluate_attribute_value_to_raw_string(else_if_value.to_owned(), current_component);
+            let evaluated_else_if_value_result = evaluate_attribute_value_to_raw_string(
+                else_if_value.to_owned(),
+                current_component,
+                scope,
+            );
             if evaluated_else_if_value_result.is_ok() {
                 let evaluated_else_if_value = evaluated_else_if_value_result.unwrap();
                 let res = is_input_true_literal(&evaluated_else_if_value);
@@ -260,32 +709,270 @@ pub mod dom_mod {
                 }
                 return Ok((res, new_state));
             } else {
-                return Err(evaluated_else_if_value_result.unwrap_err());
+                return Err(locate_node_error(
+                    evaluated_else_if_value_result.unwrap_err(),
+                    current_root,
+                    Some(RENDER_ELSE_IF_ATTRIBUTE_NAME),
+                    current_component,
+                ));
             }
         } else if else_.is_some() {
             match if_state_expr {
                 IfExprState::False => return Ok((true, IfExprState::NotReached)),
                 IfExprState::True => return Ok((false, IfExprState::NotReached)),
                 IfExprState::NotReached => {
-                    return Err(Error::ParsingError(format!(
-                        "Didn't expect a `{RENDER_ELSE_ATTRIBUTE_NAME}` attribute."
-                    )))
+                    return Err(locate_node_error(
+                        Error::ParsingError(format!(
+                            "Didn't expect a `{RENDER_ELSE_ATTRIBUTE_NAME}` attribute."
+                        )),
+                        current_root,
+                        Some(RENDER_ELSE_ATTRIBUTE_NAME),
+                        current_component,
+                    ))
                 }
             }
         }
         return Ok((true, if_state_expr));
     }
 
-    fn run_mount_effects(component: &mut Component) -> Result<(), Error> {
+    /// Inserts `node` into `parent`, right before `before`; or at the end of `parent` if `before` is
+    /// `None`. Returns an `Err` variant if the DOM refuses the insertion.
+    fn insert_node(parent: &Node, node: &Node, before: Option<&Node>) -> Result<(), Error> {
+        let insert_result = parent.insert_before(node, before);
+        if insert_result.is_err() {
+            return Err(Error::DomError(insert_result.unwrap_err()));
+        }
+        Ok(())
+    }
+
+    /// Removes `node` from its parent, if it has one.
+    fn remove_node(node: &Node) -> Result<(), Error> {
+        let parent = node.parent_node();
+        if parent.is_none() {
+            return Ok(());
+        }
+        let remove_result = parent.unwrap().remove_child(node);
+        if remove_result.is_err() {
+            return Err(Error::DomError(remove_result.unwrap_err()));
+        }
+        Ok(())
+    }
+
+    /// Sets already evaluated attributes on the provided element. `key` is left out, as it only
+    /// identifies the node among its siblings, and so are event attributes, which become listeners
+    /// instead. Returns an `Err` if setting any of the attributes fails.
+    fn set_attributes(
+        element: &Element,
+        attributes: &HashMap<String, String>,
+    ) -> Result<(), Error> {
+        for (key, value) in attributes {
+            if is_input_key_attribute(key) || is_input_event_attribute(key) {
+                continue;
+            }
+            let set_attribute_result = element.set_attribute(key, value);
+            if set_attribute_result.is_err() {
+                return Err(Error::DomError(set_attribute_result.unwrap_err()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Attaches a listener to `element` for each of the evaluated event attributes. Returns an `Err` if
+    /// attaching any of them fails.
+    fn attach_listeners(
+        element: &Element,
+        attributes: &HashMap<String, String>,
+    ) -> Result<Vec<Listener>, Error> {
+        let mut listeners: Vec<Listener> = Vec::new();
+        for (key, value) in attributes {
+            if !is_input_event_attribute(key) {
+                continue;
+            }
+            let listener_result = attach_listener(element, key, value);
+            if listener_result.is_err() {
+                return Err(listener_result.unwrap_err());
+            }
+            listeners.push(listener_result.unwrap());
+        }
+        Ok(listeners)
+    }
+
+    /// Detaches the listener of `element` which was attached for the event attribute `key`, if there
+    /// is one.
+    fn detach_listener_of(
+        element: &Element,
+        listeners: &mut Vec<Listener>,
+        key: &str,
+    ) -> Result<(), Error> {
+        let event = get_event_name(key);
+        let position = listeners
+            .iter()
+            .position(|listener| listener.event == event);
+        if position.is_none() {
+            return Ok(());
+        }
+        detach_listener(element, listeners.remove(position.unwrap()))
+    }
+
+    /// Returns the namespace an element named `tag_name` must be created in, when it is inserted into
+    /// `parent`; `None` means an HTML element. `svg` and `math` elements start their own namespace,
+    /// which their descendants inherit, except for the children of `foreignObject`. A known namespace
+    /// prefix, like in `svg:rect`, puts the element in that namespace regardless of its parent.
+    fn get_namespace(tag_name: &str, parent: &Node) -> Option<String> {
+        let (prefix, local_name) = tag_name.split_once(':').unwrap_or(("", tag_name));
+        let namespace_owner = if prefix.is_empty() {
+            local_name
+        } else {
+            prefix
+        };
+        let own_namespace = match namespace_owner {
+            SVG_TAG_NAME => Some(SVG_NAMESPACE.to_owned()),
+            MATH_TAG_NAME => Some(MATHML_NAMESPACE.to_owned()),
+            _ => None,
+        };
+        if own_namespace.is_some() {
+            return own_namespace;
+        }
+        let parent_element = parent.dyn_ref::<Element>()?;
+        if parent_element.local_name() == FOREIGN_OBJECT_TAG_NAME {
+            return None;
+        }
+        parent_element
+            .namespace_uri()
+            .filter(|namespace| namespace == SVG_NAMESPACE || namespace == MATHML_NAMESPACE)
+    }
+
+    /// Creates the DOM nodes of an evaluated virtual node and inserts them into `parent`, right before
+    /// `before`. Nodes are attached to the DOM before their children are built, so effects of nested
+    /// components can already find their own elements in the document.
+    /// Returns an `Err` variant which explains what went wrong, `Ok` containing the created nodes otherwise.
+    fn build_node(
+        current_root: &VirtualNode,
+        parent: &Node,
+        before: Option<&Node>,
+        document: &Document,
+    ) -> Result<RenderedNode, Error> {
+        match &current_root.node_type {
+            NodeType::Tag(tag_name) => {
+                let namespace = get_namespace(tag_name, parent);
+                let new_element_result = if namespace.is_some() {
+                    document.create_element_ns(namespace.as_deref(), tag_name)
+                } else {
+                    document.create_element(tag_name)
+                };
+                if new_element_result.is_err() {
+                    return Err(Error::DomError(new_element_result.unwrap_err()));
+                }
+                let new_element = new_element_result.unwrap();
+
+                let set_attributes_result = set_attributes(&new_element, &current_root.attributes);
+                if set_attributes_result.is_err() {
+                    return Err(set_attributes_result.unwrap_err());
+                }
+                let listeners_result = attach_listeners(&new_element, &current_root.attributes);
+                if listeners_result.is_err() {
+                    return Err(listeners_result.unwrap_err());
+                }
+
+                let insert_result = insert_node(parent, &new_element, before);
+                if insert_result.is_err() {
+                    return Err(insert_result.unwrap_err());
+                }
+
+                let mut children: Vec<RenderedNode> = Vec::new();
+                for child in &current_root.children {
+                    let child_result = build_node(child, &new_element, None, document);
+                    if child_result.is_err() {
+                        return Err(child_result.unwrap_err());
+                    }
+                    children.push(child_result.unwrap());
+                }
+                Ok(RenderedNode::Element {
+                    element: new_element,
+                    children,
+                    listeners: listeners_result.unwrap(),
+                })
+            }
+            NodeType::Text(text) => {
+                let text_element_result = Text::new_with_data(text);
+                if text_element_result.is_err() {
+                    return Err(Error::DomError(text_element_result.unwrap_err()));
+                }
+                let text_element = text_element_result.unwrap();
+
+                let insert_result = insert_node(parent, &text_element, before);
+                if insert_result.is_err() {
+                    return Err(insert_result.unwrap_err());
+                }
+                Ok(RenderedNode::Text(text_element))
+            }
+            NodeType::Comment(comment) => {
+                let comment_node = document.create_comment(comment);
+                let insert_result = insert_node(parent, &comment_node, before);
+                if insert_result.is_err() {
+                    return Err(insert_result.unwrap_err());
+                }
+                Ok(RenderedNode::Comment(comment_node))
+            }
+            NodeType::Fragment => Err(Error::_InvestigationNeeded(
+                "Fragments are supposed to be replaced by their children while evaluating."
+                    .to_owned(),
+            )),
+            NodeType::ScopedSlot(..) => Err(Error::_InvestigationNeeded(
+                "Scoped slot content is supposed to be evaluated by the slot which renders it."
+                    .to_owned(),
+            )),
+            NodeType::Component(component) => {
+                let props_result = get_props_from_attributes(&current_root.attributes);
+                if props_result.is_err() {
+                    return Err(props_result.unwrap_err());
+                }
+                let mut component = component.clone();
+                component.set_props(props_result.unwrap());
+                let mount_result = mount_component(
+                    component,
+                    current_root.children.clone(),
+                    parent,
+                    before,
+                    document,
+                );
+                if mount_result.is_err() {
+                    return Err(mount_result.unwrap_err());
+                }
+                Ok(RenderedNode::Component(mount_result.unwrap()))
+            }
+        }
+    }
+
+    /// Runs `effect` of the mounted instance with the given id, whose copy of the component is
+    /// `component`. The effect runs of the instance are taken out of it while the effects run, so that
+    /// effects can call back into retort.
+    fn run_instance_effects(
+        instance_id: usize,
+        effect: Effects,
+        component: &mut Component,
+        prev_state: &JsValue,
+        prev_props: &JsValue,
+    ) -> Result<(), Error> {
+        let mut effect_runs = take_effect_runs(instance_id);
+        let effects_result =
+            effects_runner(effect, component, &mut effect_runs, prev_state, prev_props);
+        restore_effect_runs(instance_id, effect_runs);
+        effects_result
+    }
+
+    fn run_mount_effects(instance_id: usize, component: &mut Component) -> Result<(), Error> {
         let prev_state = &component.state_parsed();
         let prev_props = &component.props_parsed();
-        let did_mount_res = effects_runner(
+        let did_mount_res = run_instance_effects(
+            instance_id,
             Effects::ComponentDidMount,
             component,
             prev_state,
             prev_props,
         );
-        let update_res = effects_runner(
+        let update_res = run_instance_effects(
+            instance_id,
             Effects::ComponentDidUpdate,
             component,
             prev_state,
@@ -302,132 +989,597 @@ pub mod dom_mod {
         }
     }
 
-    /// Constructs DOM using the provided virtual node and component as a root. Returns `Ok` variant
-    /// if no errors are encountered while building DOM; an `Err` variant otherwise, explaining what
-    /// went wrong.
-    fn construct_dom(
-        current_root: VirtualNode,
-        current_component: &mut Component,
-        parent: &Element,
+    /// Mounts a new instance of `component`: evaluates its vdom, inserts the resulting DOM nodes into
+    /// `parent` right before `before` and runs its mount effects. If those effects update the state,
+    /// the instance is patched right away. `slot` is what its `<slot />` renders.
+    /// Returns an `Ok` variant containing the id of the mounted instance, `Err` otherwise.
+    fn mount_component(
+        mut component: Component,
+        slot: Vec<VirtualNode>,
+        parent: &Node,
+        before: Option<&Node>,
         document: &Document,
-    ) -> Result<(), Error> {
-        let node_type = current_root.node_type.clone();
-        let res;
-        match node_type {
-            NodeType::Component(mut component) => {
-                let render_node_result: Result<(bool, IfExprState), Error> = should_node_render(
-                    *component.get_vdom().clone(),
-                    IfExprState::NotReached,
-                    &mut component,
-                );
-                if render_node_result.is_err() {
-                    return Err(render_node_result.unwrap_err());
-                }
-                let should_add = render_node_result.unwrap().0;
-                if !should_add {
-                    return Ok(());
+    ) -> Result<usize, Error> {
+        // instances of the same component share their state, as there is only one JavaScript object
+        // whose `set_state` can be called.
+        let mounted_state = get_mounted_state(component.get_id());
+        if mounted_state.is_some() {
+            component.set_state_string(mounted_state.unwrap());
+        }
+
+        let anchor = document.create_comment("");
+        let insert_result = insert_node(parent, &anchor, before);
+        if insert_result.is_err() {
+            return Err(insert_result.unwrap_err());
+        }
+
+        let roots_result = evaluate_component_vdom(&component, &slot);
+        if roots_result.is_err() {
+            return Err(roots_result.unwrap_err());
+        }
+        let roots = roots_result.unwrap();
+        let mut rendered: Vec<RenderedNode> = Vec::new();
+        for root in &roots {
+            let root_result = build_node(root, parent, Some(&anchor), document);
+            if root_result.is_err() {
+                return Err(root_result.unwrap_err());
+            }
+            rendered.push(root_result.unwrap());
+        }
+
+        let instance_id = register_instance(MountedComponent {
+            component: component.clone(),
+            slot,
+            roots,
+            rendered,
+            anchor,
+            effect_runs: Vec::new(),
+        });
+
+        let state_before_effects = component.get_state().clone();
+        let initial_effect_call_result = run_mount_effects(instance_id, &mut component);
+        if initial_effect_call_result.is_err() {
+            return Err(initial_effect_call_result.unwrap_err());
+        }
+        if *component.get_state() != state_before_effects {
+            let update_result = update_mounted_component(&component);
+            if update_result.is_err() {
+                return Err(update_result.unwrap_err());
+            }
+        }
+        Ok(instance_id)
+    }
+
+    /// Unmounts the instances which live inside `rendered`: their `ComponentWillUnmount` effects run,
+    /// children before parents, and they are dropped from the registry. Event listeners of the
+    /// elements are detached as well, but DOM nodes are left in the document.
+    /// Every node is released even if releasing one of them fails; the first error is returned.
+    fn release_rendered(rendered: RenderedNode) -> Result<(), Error> {
+        let mut release_result: Result<(), Error> = Ok(());
+        let mut keep_first_error = |result: Result<(), Error>| {
+            if release_result.is_ok() && result.is_err() {
+                release_result = result;
+            }
+        };
+        match rendered {
+            RenderedNode::Element {
+                element,
+                children,
+                listeners,
+            } => {
+                for listener in listeners {
+                    keep_first_error(detach_listener(&element, listener));
                 }
-                res = construct_dom(
-                    *component.get_vdom().clone(),
-                    &mut component,
-                    parent,
-                    document,
-                );
-                let initial_effect_call_result = run_mount_effects(&mut component);
-                if initial_effect_call_result.is_err() {
-                    return Err(initial_effect_call_result.unwrap_err());
+                for child in children {
+                    keep_first_error(release_rendered(child));
                 }
             }
-            NodeType::Tag(tag_name) => {
-                res = construct_tag(
-                    current_root,
-                    current_component,
-                    parent,
-                    document,
-                    tag_name.to_owned(),
-                );
+            RenderedNode::Text(_) | RenderedNode::Comment(_) => {}
+            RenderedNode::Component(instance_id) => {
+                let mounted_option = take_instance(instance_id);
+                if mounted_option.is_some() {
+                    let mut mounted = mounted_option.unwrap();
+                    for root in mounted.rendered {
+                        keep_first_error(release_rendered(root));
+                    }
+                    let mut component = mounted.component;
+                    let prev_state = component.state_parsed();
+                    let prev_props = component.props_parsed();
+                    keep_first_error(effects_runner(
+                        Effects::ComponentWillUnmount,
+                        &mut component,
+                        &mut mounted.effect_runs,
+                        &prev_state,
+                        &prev_props,
+                    ));
+                }
             }
-            NodeType::Text(text) => {
-                res = construct_text(&text, parent, current_component);
+        }
+        release_result
+    }
+
+    /// Unmounts the instances inside `rendered` and removes its DOM nodes from the document. Nodes are
+    /// removed even if releasing them fails.
+    fn remove_rendered(rendered: RenderedNode) -> Result<(), Error> {
+        let nodes = dom_nodes(&rendered);
+        let release_result = release_rendered(rendered);
+        for node in nodes {
+            let remove_result = remove_node(&node);
+            if remove_result.is_err() {
+                return Err(remove_result.unwrap_err());
             }
         }
-        return res;
+        release_result
     }
 
-    /// Encapsulates the logic of preparing arguments for `self::construct_dom` function
-    pub fn construct_dom_wrapper(root_component: &mut Component) -> Result<(), Error> {
-        let document_result = get_document();
-        if document_result.is_err() {
-            return Err(document_result.unwrap_err());
+    /// Builds the DOM nodes of `new` in place of the ones in `rendered`, then removes the old ones.
+    fn replace_rendered(
+        rendered: &mut RenderedNode,
+        new: &VirtualNode,
+        document: &Document,
+    ) -> Result<(), Error> {
+        let old_nodes = dom_nodes(rendered);
+        let first_node = old_nodes.first();
+        let parent = first_node.and_then(|node| node.parent_node());
+        if parent.is_none() {
+            return Err(Error::ReferenceError(
+                "Could not find the parent of a node which was supposed to be replaced.".to_owned(),
+            ));
+        }
+        let new_rendered_result = build_node(new, &parent.unwrap(), first_node, document);
+        if new_rendered_result.is_err() {
+            return Err(new_rendered_result.unwrap_err());
         }
+        let old_rendered = std::mem::replace(rendered, new_rendered_result.unwrap());
+        return remove_rendered(old_rendered);
+    }
 
-        let parent_result = get_app_wrapper();
-        if parent_result.is_err() {
-            return Err(parent_result.unwrap_err());
+    /// Returns the error which is used when a patch doesn't match the nodes it is applied to.
+    fn get_mismatched_patch_error(patch: &Patch) -> Error {
+        Error::_InvestigationNeeded(format!(
+            "The following patch doesn't match the DOM it was applied to: {:?}",
+            patch
+        ))
+    }
+
+    /// Returns the first DOM node among `siblings`, or `after` if they have none. A node which is
+    /// inserted right before `siblings` must be inserted before the returned node.
+    fn get_reference_node(siblings: &[RenderedNode], after: Option<&Node>) -> Option<Node> {
+        for sibling in siblings {
+            let nodes = dom_nodes(sibling);
+            if !nodes.is_empty() {
+                return Some(nodes[0].clone());
+            }
         }
+        after.cloned()
+    }
 
-        let document = document_result.unwrap();
-        let parent = parent_result.unwrap();
+    /// Follows `path` from `siblings` and returns the rendered node it points to; `None` is
+    /// returned if there is no such node.
+    fn locate_rendered<'a>(
+        siblings: &'a mut Vec<RenderedNode>,
+        path: &[usize],
+    ) -> Option<&'a mut RenderedNode> {
+        let (index, rest) = path.split_first()?;
+        let rendered = siblings.get_mut(*index)?;
+        if rest.is_empty() {
+            return Some(rendered);
+        }
+        match rendered {
+            RenderedNode::Element { children, .. } => locate_rendered(children, rest),
+            _ => None,
+        }
+    }
 
-        let render_node_result: Result<(bool, IfExprState), Error> = should_node_render(
-            *root_component.get_vdom().clone(),
-            IfExprState::NotReached,
-            root_component,
-        );
-        if render_node_result.is_err() {
-            return Err(render_node_result.unwrap_err());
+    /// Returns the children of the rendered node at `parent_path`, the DOM node they live in, and the
+    /// DOM node they must be inserted before when they are appended. An empty `parent_path` refers to
+    /// `roots` themselves, which live in `root_parent` right before `after`.
+    fn locate_children<'a>(
+        roots: &'a mut Vec<RenderedNode>,
+        root_parent: &Node,
+        after: Option<&Node>,
+        parent_path: &[usize],
+    ) -> Option<(&'a mut Vec<RenderedNode>, Node, Option<Node>)> {
+        if parent_path.is_empty() {
+            return Some((roots, root_parent.clone(), after.cloned()));
         }
-        let should_add = render_node_result.unwrap().0;
-        if !should_add {
+        match locate_rendered(roots, parent_path)? {
+            RenderedNode::Element {
+                element, children, ..
+            } => Some((children, element.clone().into(), None)),
+            _ => None,
+        }
+    }
+
+    /// Sets a single prop of the mounted instance with the given id to `value`, which is a JSON
+    /// string; or removes it if `value` is `None`. The instance is not re-rendered here; instead, it is
+    /// added to `updated_instances` along with its props before the first change, so it is re-rendered
+    /// once, after all patches are applied.
+    fn update_instance_prop(
+        instance_id: usize,
+        key: &String,
+        value: Option<&String>,
+        updated_instances: &mut Vec<(usize, String)>,
+    ) -> Result<(), Error> {
+        let mounted_option = take_instance(instance_id);
+        if mounted_option.is_none() {
             return Ok(());
         }
+        let mut mounted = mounted_option.unwrap();
+        let prev_props = mounted.component.get_props().clone();
+        let props_result = get_props_with_prop(&prev_props, key, value);
+        if props_result.is_ok() {
+            mounted
+                .component
+                .set_props(props_result.as_ref().unwrap().to_owned());
+        }
+        restore_instance(instance_id, mounted);
+        if props_result.is_err() {
+            return Err(props_result.unwrap_err());
+        }
+        if !updated_instances.iter().any(|(id, _)| *id == instance_id) {
+            updated_instances.push((instance_id, prev_props));
+        }
+        Ok(())
+    }
 
-        let construct_dom_result = construct_dom(
-            *root_component.get_vdom().clone(),
-            root_component,
-            &parent,
-            &document,
-        );
-        if construct_dom_result.is_err() {
-            let msg = construct_dom_result.unwrap_err();
-            match msg {
-                Error::DomError(err) => log_1(&err),
-                Error::EvaluationError(err) => {
-                    let a = to_value(&err).unwrap();
-                    log_1(&a);
+    /// Replaces the evaluated children which were passed to the mounted instance with the given id.
+    /// Like `update_instance_prop`, the instance is only added to `updated_instances` here.
+    fn update_instance_slot(
+        instance_id: usize,
+        children: &[VirtualNode],
+        updated_instances: &mut Vec<(usize, String)>,
+    ) -> Result<(), Error> {
+        let mounted_option = take_instance(instance_id);
+        if mounted_option.is_none() {
+            return Ok(());
+        }
+        let mut mounted = mounted_option.unwrap();
+        let prev_props = mounted.component.get_props().clone();
+        mounted.slot = children.to_vec();
+        restore_instance(instance_id, mounted);
+        if !updated_instances.iter().any(|(id, _)| *id == instance_id) {
+            updated_instances.push((instance_id, prev_props));
+        }
+        Ok(())
+    }
+
+    /// Re-renders the mounted instances whose props were changed, then runs their `ComponentDidUpdate`
+    /// effects with the props they had before. If those effects update the state, every instance of
+    /// the component is patched once more.
+    fn rerender_updated_instances(
+        updated_instances: Vec<(usize, String)>,
+        document: &Document,
+    ) -> Result<(), Error> {
+        for (instance_id, prev_props) in updated_instances {
+            let mounted_option = take_instance(instance_id);
+            if mounted_option.is_none() {
+                continue;
+            }
+            let mut mounted = mounted_option.unwrap();
+            let rerender_result = rerender_instance(&mut mounted, document);
+            let mut component = mounted.component.clone();
+            restore_instance(instance_id, mounted);
+            if rerender_result.is_err() {
+                return Err(rerender_result.unwrap_err());
+            }
+
+            let prev_state = component.state_parsed();
+            let prev_props = JSON::parse(&prev_props).unwrap_or(JsValue::null());
+            let state_before_effects = component.get_state().clone();
+            let effects_result = run_instance_effects(
+                instance_id,
+                Effects::ComponentDidUpdate,
+                &mut component,
+                &prev_state,
+                &prev_props,
+            );
+            if effects_result.is_err() {
+                return Err(effects_result.unwrap_err());
+            }
+            if *component.get_state() != state_before_effects {
+                let update_result = update_mounted_component(&component);
+                if update_result.is_err() {
+                    return Err(update_result.unwrap_err());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies a single patch to the DOM nodes in `roots` and keeps `roots` in sync with the DOM.
+    /// `roots` live in `root_parent`, right before `after`. Attribute patches on a component change
+    /// its props; such instances are collected in `updated_instances`.
+    fn apply_patch(
+        patch: &Patch,
+        roots: &mut Vec<RenderedNode>,
+        root_parent: &Node,
+        after: Option<&Node>,
+        document: &Document,
+        updated_instances: &mut Vec<(usize, String)>,
+    ) -> Result<(), Error> {
+        match patch {
+            Patch::Insert {
+                parent,
+                index,
+                node,
+            } => {
+                let located = locate_children(roots, root_parent, after, parent);
+                if located.is_none() || *index > located.as_ref().unwrap().0.len() {
+                    return Err(get_mismatched_patch_error(patch));
                 }
-                Error::TypeError(err) => {
-                    let a = to_value(&err).unwrap();
-                    log_1(&a);
+                let (children, parent_node, after_node) = located.unwrap();
+                let reference = get_reference_node(&children[*index..], after_node.as_ref());
+                let build_result = build_node(node, &parent_node, reference.as_ref(), document);
+                if build_result.is_err() {
+                    return Err(build_result.unwrap_err());
                 }
-                Error::ResolveError(err) => {
-                    let a = to_value(&err).unwrap();
-                    log_1(&a);
+                children.insert(*index, build_result.unwrap());
+                Ok(())
+            }
+            Patch::Remove { parent, index } => {
+                let located = locate_children(roots, root_parent, after, parent);
+                if located.is_none() || *index >= located.as_ref().unwrap().0.len() {
+                    return Err(get_mismatched_patch_error(patch));
                 }
-                Error::ParsingError(err) => {
-                    let a = to_value(&err).unwrap();
-                    log_1(&a);
+                let (children, _, _) = located.unwrap();
+                return remove_rendered(children.remove(*index));
+            }
+            Patch::Replace { path, node } => {
+                let rendered = locate_rendered(roots, path);
+                if rendered.is_none() {
+                    return Err(get_mismatched_patch_error(patch));
                 }
-                Error::ReferenceError(err) => {
-                    let a = to_value(&err).unwrap();
-                    log_1(&a);
+                return replace_rendered(rendered.unwrap(), node, document);
+            }
+            Patch::SetAttribute { path, key, value } => match locate_rendered(roots, path) {
+                Some(RenderedNode::Element {
+                    element, listeners, ..
+                }) if is_input_event_attribute(key) => {
+                    let detach_result = detach_listener_of(element, listeners, key);
+                    if detach_result.is_err() {
+                        return Err(detach_result.unwrap_err());
+                    }
+                    let listener_result = attach_listener(element, key, value);
+                    if listener_result.is_err() {
+                        return Err(listener_result.unwrap_err());
+                    }
+                    listeners.push(listener_result.unwrap());
+                    Ok(())
                 }
-                Error::SerdeWasmBindgenError(err) => {
-                    let a = err.to_string();
-                    let a = to_value(&a).unwrap();
-                    log_1(&a);
+                Some(RenderedNode::Element { element, .. }) => {
+                    let set_attribute_result = element.set_attribute(key, value);
+                    if set_attribute_result.is_err() {
+                        return Err(Error::DomError(set_attribute_result.unwrap_err()));
+                    }
+                    Ok(())
                 }
-
-                _ => {
-                    log_1(&JsValue::from_str("err"));
+                Some(RenderedNode::Component(instance_id)) => {
+                    update_instance_prop(*instance_id, key, Some(value), updated_instances)
+                }
+                _ => Err(get_mismatched_patch_error(patch)),
+            },
+            Patch::RemoveAttribute { path, key } => match locate_rendered(roots, path) {
+                Some(RenderedNode::Element {
+                    element, listeners, ..
+                }) if is_input_event_attribute(key) => detach_listener_of(element, listeners, key),
+                Some(RenderedNode::Element { element, .. }) => {
+                    let remove_attribute_result = element.remove_attribute(key);
+                    if remove_attribute_result.is_err() {
+                        return Err(Error::DomError(remove_attribute_result.unwrap_err()));
+                    }
+                    Ok(())
+                }
+                Some(RenderedNode::Component(instance_id)) => {
+                    update_instance_prop(*instance_id, key, None, updated_instances)
+                }
+                _ => Err(get_mismatched_patch_error(patch)),
+            },
+            Patch::SetText { path, text } => match locate_rendered(roots, path) {
+                Some(RenderedNode::Text(text_node)) => {
+                    text_node.set_data(text);
+                    Ok(())
+                }
+                _ => Err(get_mismatched_patch_error(patch)),
+            },
+            Patch::Move { parent, from, to } => {
+                let located = locate_children(roots, root_parent, after, parent);
+                if located.is_none()
+                    || *from >= located.as_ref().unwrap().0.len()
+                    || *to >= located.as_ref().unwrap().0.len()
+                {
+                    return Err(get_mismatched_patch_error(patch));
                 }
+                let (children, parent_node, after_node) = located.unwrap();
+                let moved = children.remove(*from);
+                let reference = get_reference_node(&children[*to..], after_node.as_ref());
+                // inserting a node which is already in the DOM moves it, so its state(focus, input
+                // values, etc) is kept.
+                for node in dom_nodes(&moved) {
+                    let insert_result = insert_node(&parent_node, &node, reference.as_ref());
+                    if insert_result.is_err() {
+                        return Err(insert_result.unwrap_err());
+                    }
+                }
+                children.insert(*to, moved);
+                Ok(())
+            }
+            Patch::SetSlot { path, children } => match locate_rendered(roots, path) {
+                Some(RenderedNode::Component(instance_id)) => {
+                    update_instance_slot(*instance_id, children, updated_instances)
+                }
+                _ => Err(get_mismatched_patch_error(patch)),
+            },
+        }
+    }
+
+    /// Applies `patches` in order to the DOM nodes in `roots`, which live in `root_parent` right
+    /// before `after`. Nested components whose props were changed are re-rendered afterwards.
+    fn apply_patches(
+        patches: &Vec<Patch>,
+        roots: &mut Vec<RenderedNode>,
+        root_parent: &Node,
+        after: Option<&Node>,
+        document: &Document,
+    ) -> Result<(), Error> {
+        let mut updated_instances: Vec<(usize, String)> = Vec::new();
+        for patch in patches {
+            let apply_result = apply_patch(
+                patch,
+                roots,
+                root_parent,
+                after,
+                document,
+                &mut updated_instances,
+            );
+            if apply_result.is_err() {
+                return Err(apply_result.unwrap_err());
+            }
+        }
+        rerender_updated_instances(updated_instances, document)
+    }
+
+    /// Re-evaluates the vdom of a mounted instance against its current state and props, diffs it with
+    /// the previous one and applies the resulting patches to its DOM nodes.
+    fn rerender_instance(mounted: &mut MountedComponent, document: &Document) -> Result<(), Error> {
+        let new_roots_result = evaluate_component_vdom(&mounted.component, &mounted.slot);
+        if new_roots_result.is_err() {
+            return Err(new_roots_result.unwrap_err());
+        }
+        let new_roots = new_roots_result.unwrap();
+        let parent = mounted.anchor.parent_node();
+        if parent.is_none() {
+            return Err(Error::ReferenceError(
+                "Could not find the parent of a mounted component.".to_owned(),
+            ));
+        }
+        let anchor: Node = mounted.anchor.clone().into();
+        let patches = diff_children(&mounted.roots, &new_roots);
+        let apply_result = apply_patches(
+            &patches,
+            &mut mounted.rendered,
+            &parent.unwrap(),
+            Some(&anchor),
+            document,
+        );
+        mounted.roots = new_roots;
+        return apply_result;
+    }
+
+    /// Returns a copy of a mounted instance of the component with the given id, if there is one.
+    pub fn get_mounted_component(component_id: u32) -> Option<Component> {
+        let instance_id = instances_of(component_id).into_iter().next();
+        if instance_id.is_none() {
+            return None;
+        }
+        with_instance(instance_id.unwrap(), |mounted| mounted.component.clone())
+    }
+
+    /// Returns the state of a mounted instance of the component with the given id, if there is one.
+    pub fn get_mounted_state(component_id: u32) -> Option<String> {
+        let instance_id = instances_of(component_id).into_iter().next();
+        if instance_id.is_none() {
+            return None;
+        }
+        with_instance(instance_id.unwrap(), |mounted| {
+            mounted.component.get_state().clone()
+        })
+    }
+
+    /// Runs the `ComponentDidUpdate` effects of `component` for every mounted instance of it, with the
+    /// props of that instance; `prev_state` is the state before the update. Instances share their
+    /// state, so the state which the effects of an instance return is what the next instance sees, and
+    /// it is left in `component`. The caller patches the DOM afterwards if the state was changed.
+    pub fn run_update_effects(
+        component: &mut Component,
+        prev_state: &JsValue,
+    ) -> Result<(), Error> {
+        for instance_id in instances_of(component.get_id()) {
+            let instance_component =
+                with_instance(instance_id, |mounted| mounted.component.clone());
+            if instance_component.is_none() {
+                continue;
+            }
+            let mut instance_component = instance_component.unwrap();
+            instance_component.set_state_string(component.get_state().clone());
+            let prev_props = instance_component.props_parsed();
+            let effects_result = run_instance_effects(
+                instance_id,
+                Effects::ComponentDidUpdate,
+                &mut instance_component,
+                prev_state,
+                &prev_props,
+            );
+            component.set_state_string(instance_component.get_state().clone());
+            if effects_result.is_err() {
+                return Err(effects_result.unwrap_err());
+            }
+        }
+        Ok(())
+    }
+
+    /// Copies the state of `component` to every mounted instance of it and patches their DOM nodes.
+    /// Components that are not mounted yet are ignored.
+    pub fn update_mounted_component(component: &Component) -> Result<(), Error> {
+        let instance_ids = instances_of(component.get_id());
+        if instance_ids.is_empty() {
+            return Ok(());
+        }
+        let document_result = get_document();
+        if document_result.is_err() {
+            return Err(document_result.unwrap_err());
+        }
+        let document = document_result.unwrap();
+        for instance_id in instance_ids {
+            let mounted_option = take_instance(instance_id);
+            if mounted_option.is_none() {
+                continue; // the instance is being patched further up the call stack.
+            }
+            let mut mounted = mounted_option.unwrap();
+            mounted
+                .component
+                .set_state_string(component.get_state().clone());
+            let rerender_result = rerender_instance(&mut mounted, &document);
+            restore_instance(instance_id, mounted);
+            if rerender_result.is_err() {
+                return Err(rerender_result.unwrap_err());
             }
         }
         Ok(())
     }
-}
 
-// rendering list of data:
-// render-for is used as a special attribute to mark the "wrapper" of rendered list.
-// render-for syntax is sth like this: render-for={"varName of state.someList"}
-// this way, `varName` becomes a value which is available in the entire subtree of the "wrapper".
+    /// Unmounts every mounted instance of `component` and removes their DOM nodes. Components that are
+    /// not mounted are ignored.
+    pub fn unmount_component(component: &Component) -> Result<(), Error> {
+        for instance_id in instances_of(component.get_id()) {
+            let remove_result = remove_rendered(RenderedNode::Component(instance_id));
+            if remove_result.is_err() {
+                return Err(remove_result.unwrap_err());
+            }
+        }
+        Ok(())
+    }
+
+    /// Encapsulates the logic of preparing arguments for `self::mount_component` function
+    pub fn construct_dom_wrapper(root_component: &Component) -> Result<(), Error> {
+        let document_result = get_document();
+        if document_result.is_err() {
+            return Err(document_result.unwrap_err());
+        }
+
+        let parent_result = get_app_wrapper();
+        if parent_result.is_err() {
+            return Err(parent_result.unwrap_err());
+        }
+
+        let document = document_result.unwrap();
+        let parent = parent_result.unwrap();
+
+        let mount_result =
+            mount_component(root_component.clone(), Vec::new(), &parent, None, &document);
+        if mount_result.is_err() {
+            return Err(mount_result.unwrap_err());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+/// Keeps track of the components that are currently on the page, along with the DOM nodes that were
+/// created for them, so that later updates can patch the DOM instead of rebuilding it.
+pub mod mounted_mod {
+    use std::{cell::RefCell, collections::HashMap};
+
+    use web_sys::{Comment, Element, Node, Text};
+
+    use crate::{
+        component::component_mod::{Component, EffectRun},
+        dom::events::events_mod::Listener,
+        parser::parser_mod::VirtualNode,
+    };
+
+    /// Mirrors an evaluated `VirtualNode` with the DOM node that was created for it. Children of a
+    /// `Component` node are not mirrored here; they belong to the mounted instance it points to.
+    /// `listeners` are the event listeners which were attached to `element` for its event attributes.
+    #[derive(Debug)]
+    pub enum RenderedNode {
+        Element {
+            element: Element,
+            children: Vec<RenderedNode>,
+            listeners: Vec<Listener>,
+        },
+        Text(Text),
+        Comment(Comment),
+        Component(usize), // id of the mounted instance
+    }
+
+    /// A component instance which has been added to the DOM. `roots` is the evaluated vdom that is
+    /// currently on the page and `rendered` holds the matching DOM nodes. Every instance ends with an
+    /// empty comment node, `anchor`, which marks where its nodes end; this lets an instance grow back
+    /// in place after all of its nodes have been removed, e.g. when its `render-if` turns false. `slot`
+    /// holds the evaluated children which were passed to the instance by its parent. `effect_runs`
+    /// keep the dependencies and cleanups of the effects with a dependency list, for this instance only.
+    pub struct MountedComponent {
+        pub component: Component,
+        pub slot: Vec<VirtualNode>,
+        pub roots: Vec<VirtualNode>,
+        pub rendered: Vec<RenderedNode>,
+        pub anchor: Comment,
+        pub effect_runs: Vec<EffectRun>,
+    }
+
+    thread_local! {
+        static MOUNTED: RefCell<HashMap<usize, MountedComponent>> = RefCell::new(HashMap::new());
+        static NEXT_INSTANCE_ID: RefCell<usize> = RefCell::new(0);
+    }
+
+    // Note that the registry is never borrowed while JavaScript code runs. Callers `take` an instance
+    // out, work on it and `restore` it afterwards; this way, effects that synchronously call back into
+    // retort can't cause a double borrow.
+
+    /// Adds a newly mounted instance to the registry and returns its id.
+    pub fn register_instance(mounted: MountedComponent) -> usize {
+        let id = NEXT_INSTANCE_ID.with(|next| {
+            let mut next = next.borrow_mut();
+            *next += 1;
+            *next
+        });
+        MOUNTED.with(|instances| instances.borrow_mut().insert(id, mounted));
+        id
+    }
+
+    /// Removes the instance with the given id from the registry and returns it, if it exists.
+    pub fn take_instance(id: usize) -> Option<MountedComponent> {
+        MOUNTED.with(|instances| instances.borrow_mut().remove(&id))
+    }
+
+    /// Puts an instance which was previously taken out using `take_instance` back to the registry.
+    pub fn restore_instance(id: usize, mounted: MountedComponent) {
+        MOUNTED.with(|instances| instances.borrow_mut().insert(id, mounted));
+    }
+
+    /// Takes the effect runs out of the instance with the given id, leaving the instance itself in the
+    /// registry, so that effects which call back into retort can still find it. An empty list is
+    /// returned if the instance is not in the registry.
+    pub fn take_effect_runs(id: usize) -> Vec<EffectRun> {
+        MOUNTED.with(|instances| {
+            instances
+                .borrow_mut()
+                .get_mut(&id)
+                .map(|mounted| std::mem::take(&mut mounted.effect_runs))
+                .unwrap_or_default()
+        })
+    }
+
+    /// Puts effect runs which were taken out using `take_effect_runs` back to the instance with the
+    /// given id. They are dropped if the instance was unmounted in the meantime.
+    pub fn restore_effect_runs(id: usize, effect_runs: Vec<EffectRun>) {
+        MOUNTED.with(|instances| {
+            if let Some(mounted) = instances.borrow_mut().get_mut(&id) {
+                mounted.effect_runs = effect_runs;
+            }
+        });
+    }
+
+    /// Returns ids of every mounted instance of the component with the given id.
+    pub fn instances_of(component_id: u32) -> Vec<usize> {
+        MOUNTED.with(|instances| {
+            let mut ids: Vec<usize> = instances
+                .borrow()
+                .iter()
+                .filter(|(_, mounted)| mounted.component.get_id() == component_id)
+                .map(|(id, _)| *id)
+                .collect();
+            ids.sort();
+            ids
+        })
+    }
+
+    /// Runs `f` with a shared reference to the instance with the given id and returns its result;
+    /// `None` is returned if the instance is not in the registry.
+    pub fn with_instance<T>(id: usize, f: impl FnOnce(&MountedComponent) -> T) -> Option<T> {
+        MOUNTED.with(|instances| instances.borrow().get(&id).map(f))
+    }
+
+    /// Returns the DOM nodes which correspond to `rendered`, in document order.
+    pub fn dom_nodes(rendered: &RenderedNode) -> Vec<Node> {
+        match rendered {
+            RenderedNode::Element { element, .. } => vec![element.clone().into()],
+            RenderedNode::Text(text) => vec![text.clone().into()],
+            RenderedNode::Comment(comment) => vec![comment.clone().into()],
+            RenderedNode::Component(id) => {
+                let nodes = with_instance(*id, |mounted| {
+                    let mut nodes: Vec<Node> =
+                        mounted.rendered.iter().flat_map(dom_nodes).collect();
+                    nodes.push(mounted.anchor.clone().into());
+                    nodes
+                });
+                nodes.unwrap_or_default()
+            }
+        }
+    }
+}
@@ -2,15 +2,20 @@
 pub mod error_mod {
     use std::fmt::Display;
     use wasm_bindgen::JsValue;
-    use web_sys::{console::error_1, Document, Element};
+    use web_sys::{
+        console::{error_1, warn_1},
+        Document, Element,
+    };
 
     use crate::{
         const_util::const_util_mod::{
-            DOM_ERROR, ERROR_SUBTITLE, ERROR_SUBTITLE_STYLES, ERROR_WRAPPER_STYLES,
-            EVALUATION_ERROR, PARSING_ERROR, REFERENCE_ERROR, RESOLVE_ERROR,
-            SERDE_WASM_BINDGEN_ERROR, TYPE_ERROR, _INVESTIGATION_NEEDED_ERROR,
+            DOM_ERROR, DUPLICATE_KEY_WARNING, ERROR_SUBTITLE, ERROR_SUBTITLE_STYLES,
+            ERROR_WRAPPER_STYLES, EVALUATION_ERROR, MAX_UPDATE_DEPTH_ERROR, MISSING_KEY_WARNING,
+            PARSING_ERROR, REFERENCE_ERROR, RESOLVE_ERROR, SERDE_WASM_BINDGEN_ERROR, TYPE_ERROR,
+            _INVESTIGATION_NEEDED_ERROR,
         },
         dom::dom_mod::{get_app_wrapper, get_document},
+        tokenizer::tokenizer_mod::Span,
     };
 
     #[derive(Debug)]
@@ -24,6 +29,7 @@ pub mod error_mod {
         DomError(JsValue),
         _InvestigationNeeded(String),
         SerdeWasmBindgenError(serde_wasm_bindgen::Error),
+        MaxUpdateDepthExceeded(usize, Vec<String>), // index of the effect which kept updating the state, and the last states it led to
     }
 
     impl Display for Error {
@@ -41,6 +47,15 @@ pub mod error_mod {
                     let full_message = indicator + ": " + &msg;
                     f.write_str(&full_message)
                 }
+
+                Error::MaxUpdateDepthExceeded(effect_index, transitions) => {
+                    let msg = format!(
+                        "The effect at index {effect_index} kept updating the state of its component. Last state transitions: {}",
+                        transitions.join(" -> ")
+                    );
+                    let full_message = indicator + ": " + &msg;
+                    f.write_str(&full_message)
+                }
                 Error::ParsingError(err)
                 | Error::EvaluationError(err)
                 | Error::ReferenceError(err)
@@ -54,6 +69,56 @@ pub mod error_mod {
         }
     }
 
+    #[derive(Debug)]
+    /// An enum which contains different types of warnings and their messages. Unlike errors, warnings
+    /// don't stop the rendering process; they only point out something that is likely a mistake.
+    pub enum Warning {
+        MissingKey(String),
+        DuplicateKey(String),
+    }
+
+    impl Display for Warning {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            let indicator = get_warning_variant_text(&self);
+            match &self {
+                Warning::MissingKey(msg) | Warning::DuplicateKey(msg) => {
+                    let full_message = indicator + ": " + msg;
+                    f.write_str(&full_message)
+                }
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    /// How bad a diagnostic is. `Error`s are what would stop parsing, had it not been recovering;
+    /// `Warning`s are mistakes which were dropped without losing anything.
+    pub enum Severity {
+        Error,
+        Warning,
+    }
+
+    #[derive(Debug)]
+    /// A problem found while recovering from errors. Like other errors, the message of `error` points
+    /// at where the problem is.
+    pub struct Diagnostic {
+        pub severity: Severity,
+        pub error: Error,
+    }
+
+    impl Display for Diagnostic {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(&self.error.to_string())
+        }
+    }
+
+    /// returns a string corresponding to `Warning` variant.
+    fn get_warning_variant_text(warning: &Warning) -> String {
+        match &warning {
+            Warning::MissingKey(_) => MISSING_KEY_WARNING.to_owned(),
+            Warning::DuplicateKey(_) => DUPLICATE_KEY_WARNING.to_owned(),
+        }
+    }
+
     /// returns a string corresponding to `Error` variant.
     fn get_variant_text(error: &Error) -> String {
         match &error {
@@ -65,6 +130,45 @@ pub mod error_mod {
             Error::ReferenceError(_) => REFERENCE_ERROR.to_owned(),
             Error::SerdeWasmBindgenError(_) => SERDE_WASM_BINDGEN_ERROR.to_owned(),
             Error::ResolveError(_) => RESOLVE_ERROR.to_owned(),
+            Error::MaxUpdateDepthExceeded(_, _) => MAX_UPDATE_DEPTH_ERROR.to_owned(),
+        }
+    }
+
+    /// Returns the location of `span` in `source`, followed by the line it starts at and a caret under
+    /// its first char.
+    fn get_location_text(span: &Span, source: &str) -> String {
+        let line_text = source.lines().nth(span.line - 1).unwrap_or("");
+        let gutter = span.line.to_string();
+        let gutter_padding = " ".repeat(gutter.len());
+        let caret_padding: String = line_text
+            .chars()
+            .take(span.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        format!(
+            " (line {}, column {})\n{gutter} | {line_text}\n{gutter_padding} | {caret_padding}^",
+            span.line, span.column
+        )
+    }
+
+    /// Appends the location of `span` in `source`, along with an excerpt of it, to the message of
+    /// `error`. Errors which don't carry a message, and spans which don't point anywhere, leave
+    /// `error` as it is.
+    pub fn locate_error(error: Error, span: &Span, source: &str) -> Error {
+        if span.line == 0 {
+            return error;
+        }
+        let location = get_location_text(span, source);
+        match error {
+            Error::ParsingError(msg) => Error::ParsingError(msg + &location),
+            Error::ReferenceError(msg) => Error::ReferenceError(msg + &location),
+            Error::TypeError(msg) => Error::TypeError(msg + &location),
+            Error::ResolveError(msg) => Error::ResolveError(msg + &location),
+            Error::EvaluationError(msg) => Error::EvaluationError(msg + &location),
+            Error::_InvestigationNeeded(msg) => Error::_InvestigationNeeded(msg + &location),
+            Error::DomError(_)
+            | Error::SerdeWasmBindgenError(_)
+            | Error::MaxUpdateDepthExceeded(_, _) => error,
         }
     }
 
@@ -113,6 +217,23 @@ pub mod error_mod {
         }
     }
 
+    /// Logs the warning to the console using `console.warn` function. Nothing is displayed in the DOM.
+    pub fn warning_handler(warning: Warning) {
+        let warning_string = warning.to_string();
+        let js_value_warning_string = JsValue::from_str(&warning_string);
+        warn_1(&js_value_warning_string);
+    }
+
+    /// Logs the diagnostic to the console, using `console.error` or `console.warn` based on its
+    /// severity. Nothing is displayed in the DOM, since the rest of the component is still rendered.
+    pub fn diagnostic_handler(diagnostic: &Diagnostic) {
+        let diagnostic_string = JsValue::from_str(&diagnostic.to_string());
+        match diagnostic.severity {
+            Severity::Error => error_1(&diagnostic_string),
+            Severity::Warning => warn_1(&diagnostic_string),
+        }
+    }
+
     /// Exposes error logging and displaying logic publicly.
     pub fn error_handler(error: Error) {
         let wrapper = get_app_wrapper();
@@ -151,6 +272,56 @@ pub mod error_mod {
             }
         }
 
+        #[test]
+        /// tests `Warning` variants, which all carry a `String` message.
+        fn test_warning_to_string() {
+            let message = String::from("Some warning message.");
+            let warnings = vec![
+                Warning::MissingKey(message.clone()),
+                Warning::DuplicateKey(message.clone()),
+            ];
+            for warning in warnings {
+                let warning_string = warning.to_string();
+                let warning_indicator = get_warning_variant_text(&warning);
+                assert!(warning_string == format!("{warning_indicator}: {message}"));
+            }
+        }
+
+        #[test]
+        /// tests the `MaxUpdateDepthExceeded` variant, which must name the effect and the transitions.
+        fn test_error_to_string_max_update_depth() {
+            let transitions = vec!["{\"n\":1}".to_owned(), "{\"n\":2}".to_owned()];
+            let error = Error::MaxUpdateDepthExceeded(3, transitions);
+            let error_string = error.to_string();
+            let error_indicator = get_variant_text(&error);
+            assert!(error_string.starts_with(&format!("{error_indicator}: ")));
+            assert!(error_string.contains("index 3"));
+            assert!(error_string.contains("{\"n\":1} -> {\"n\":2}"));
+        }
+
+        #[test]
+        /// `locate_error` must add the line and column of the span, and an excerpt of the source which
+        /// points to it.
+        fn test_locate_error() {
+            let source = "<div>\n  <p>\n    {state.x\n  </p>\n</div>";
+            let span = Span {
+                start: 17,
+                end: 25,
+                line: 3,
+                column: 5,
+            };
+            let error = locate_error(Error::EvaluationError("Oops.".to_owned()), &span, source);
+            assert!(
+                matches!(error, Error::EvaluationError(msg) if msg == "Oops. (line 3, column 5)\n3 |     {state.x\n  |     ^")
+            );
+            let error = locate_error(
+                Error::ParsingError("Oops.".to_owned()),
+                &Span::default(),
+                source,
+            );
+            assert!(matches!(error, Error::ParsingError(msg) if msg == "Oops."));
+        }
+
         wasm_bindgen_test_configure!(run_in_browser);
 
         #[wasm_bindgen_test]
@@ -25,11 +25,22 @@ pub mod js_evaluator {
         )
     }
 
+    /// Works like `get_state_props_evaluator`, except that the created anonymous function takes one
+    /// extra parameter for each of `binding_names`, after state and props. This is how loop variables
+    /// of `render-for` become available to expressions.
+    pub fn get_scoped_evaluator(function_string: String, binding_names: &Vec<String>) -> Function {
+        let function_body = USE_STRICT.to_owned() + CLOSURE + RETURN + &function_string;
+        let mut parameters = vec![STATE_PARAMETER.to_owned(), PROPS_PARAMETER.to_owned()];
+        parameters.extend(binding_names.iter().cloned());
+        Function::new_with_args(&parameters.join(","), &function_body)
+    }
+
     #[cfg(test)]
     mod tests {
         use serde_wasm_bindgen::to_value;
         use wasm_bindgen::JsValue;
         use wasm_bindgen_test::*;
+        use web_sys::js_sys::{Array, JSON};
 
         use super::*;
 
@@ -54,5 +65,79 @@ pub mod js_evaluator {
             let result = resolve_result.unwrap();
             assert!(matches!(result.as_string(), Some(val) if val == "ali is 12 years old."))
         }
+
+        #[wasm_bindgen_test]
+        /// tests that `props` is parsed into an object, and that nested values of it can be accessed
+        /// in expressions.
+        fn test_get_state_props_evaluator_nested_props() {
+            let expression =
+                String::from("`${props.user.name} lives in ${props.user.address.city}.`");
+            let evaluator = get_state_props_evaluator(expression);
+
+            let state_result = to_value("{}");
+            let props_result =
+                to_value("{\"user\":{\"name\":\"ali\",\"address\":{\"city\":\"Tehran\"}}}");
+            assert!(matches!(state_result, Ok(_)) && matches!(props_result, Ok(_)));
+
+            let resolve_result = evaluator.call2(
+                &JsValue::undefined(),
+                &state_result.unwrap(),
+                &props_result.unwrap(),
+            );
+            assert!(matches!(resolve_result, Ok(_)));
+
+            let result = resolve_result.unwrap();
+            assert!(matches!(result.as_string(), Some(val) if val == "ali lives in Tehran."))
+        }
+
+        #[wasm_bindgen_test]
+        /// tests that array props are parsed into real arrays, even when they were stringified twice.
+        fn test_get_state_props_evaluator_array_props() {
+            let expression = String::from("props.items.map(item => item.id * 2).join(\",\")");
+            let evaluator = get_state_props_evaluator(expression);
+
+            let state_result = to_value("{}");
+            let props_result = to_value("{\"items\":[{\"id\":1},{\"id\":2}]}");
+            let twice_stringified_props_result =
+                to_value("\"{\\\"items\\\":[{\\\"id\\\":1},{\\\"id\\\":2}]}\"");
+            assert!(matches!(state_result, Ok(_)));
+            let state = state_result.unwrap();
+
+            for props in [props_result, twice_stringified_props_result] {
+                assert!(matches!(props, Ok(_)));
+                let resolve_result =
+                    evaluator.call2(&JsValue::undefined(), &state, &props.unwrap());
+                assert!(matches!(resolve_result, Ok(_)));
+                let result = resolve_result.unwrap();
+                assert!(matches!(result.as_string(), Some(val) if val == "2,4"))
+            }
+        }
+
+        #[wasm_bindgen_test]
+        /// tests that the function which is returned from `get_scoped_evaluator` receives the bound
+        /// names after `state` and `props`.
+        fn test_get_scoped_evaluator() {
+            let expression = String::from("`${state.name}: ${item.title} #${index}`");
+            let evaluator =
+                get_scoped_evaluator(expression, &vec!["item".to_owned(), "index".to_owned()]);
+
+            let temp_js_value_result = to_value("{\"name\":\"ali\"}");
+            assert!(matches!(temp_js_value_result, Ok(_)));
+            let temp_js_value = temp_js_value_result.unwrap();
+
+            let item_result = JSON::parse("{\"title\":\"first\"}");
+            assert!(matches!(item_result, Ok(_)));
+            let arguments = Array::of4(
+                &temp_js_value,
+                &temp_js_value,
+                &item_result.unwrap(),
+                &JsValue::from_f64(0.0),
+            );
+            let resolve_result = evaluator.apply(&JsValue::undefined(), &arguments);
+            assert!(matches!(resolve_result, Ok(_)));
+
+            let result = resolve_result.unwrap();
+            assert!(matches!(result.as_string(), Some(val) if val == "ali: first #0"))
+        }
     }
 }
@@ -2,20 +2,124 @@ mod js_evaluator;
 mod util;
 pub mod evaluator_mod {
 
-    use super::js_evaluator::js_evaluator::get_state_props_evaluator;
+    use super::js_evaluator::js_evaluator::{get_scoped_evaluator, get_state_props_evaluator};
     use super::util::evaluator_util::*;
     use serde_wasm_bindgen::{from_value, to_value};
+    use std::rc::Rc;
     use wasm_bindgen::JsValue;
+    use web_sys::js_sys::{Array, JSON};
 
     use crate::{
         component::component_mod::Component,
         const_util::const_util_mod::{
-            is_input_close_curly_bracket, is_input_open_curly_bracket, NULL_LITERAL,
-            OPEN_CURLY_BRACKET, UNDEFINED_LITERAL,
+            is_input_reserved_parameter, LOOP_SEPARATOR, NULL_LITERAL, RENDER_FOR_ATTRIBUTE_NAME,
+            SLOT_SCOPE_ATTRIBUTE_NAME, UNDEFINED_LITERAL,
         },
         error::error_mod::Error,
+        parser::parser_mod::VirtualNode,
     };
 
+    /// Names which are bound by the `render-for` ancestors of the node that is being evaluated, along
+    /// with their values. Expressions can refer to these names just like they refer to `state`.
+    /// `slot` holds the children which were passed to the component that is being evaluated; they are
+    /// already evaluated in the context of its parent.
+    #[derive(Debug, Clone, Default)]
+    pub struct Scope {
+        bindings: Vec<(String, JsValue)>,
+        slot: Rc<Vec<VirtualNode>>,
+    }
+
+    impl Scope {
+        pub fn new() -> Self {
+            Scope {
+                bindings: Vec::new(),
+                slot: Rc::new(Vec::new()),
+            }
+        }
+
+        /// Returns a copy of this scope whose slot holds `children`.
+        pub fn with_slot(&self, children: Vec<VirtualNode>) -> Scope {
+            Scope {
+                bindings: self.bindings.clone(),
+                slot: Rc::new(children),
+            }
+        }
+
+        pub fn get_slot(&self) -> &Vec<VirtualNode> {
+            &self.slot
+        }
+
+        /// Returns a copy of this scope in which `name` is bound to `value`. A binding with the same
+        /// name, which belongs to an outer loop, is shadowed.
+        pub fn with_binding(&self, name: &str, value: JsValue) -> Scope {
+            let mut bindings: Vec<(String, JsValue)> = self
+                .bindings
+                .iter()
+                .filter(|(bound_name, _)| bound_name != name)
+                .cloned()
+                .collect();
+            bindings.push((name.to_owned(), value));
+            Scope {
+                bindings,
+                slot: self.slot.clone(),
+            }
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.bindings.is_empty()
+        }
+
+        pub fn get_names(&self) -> Vec<String> {
+            self.bindings
+                .iter()
+                .map(|(name, _)| name.to_owned())
+                .collect()
+        }
+
+        pub fn get_values(&self) -> Vec<JsValue> {
+            self.bindings
+                .iter()
+                .map(|(_, value)| value.clone())
+                .collect()
+        }
+
+        /// Returns the bindings of this scope with their values as JSON strings, so they can be kept
+        /// for evaluating expressions later. Values which JSON can't represent, like `undefined`, are
+        /// left out. The `Err` variant holds the name of a binding whose value couldn't be converted.
+        pub fn get_stringified_bindings(&self) -> Result<Vec<(String, String)>, String> {
+            let mut bindings: Vec<(String, String)> = Vec::new();
+            for (name, value) in &self.bindings {
+                let stringified_value = JSON::stringify(value);
+                if stringified_value.is_err() {
+                    return Err(name.to_owned());
+                }
+                let stringified_value = stringified_value.unwrap().as_string();
+                if stringified_value.is_some() {
+                    bindings.push((name.to_owned(), stringified_value.unwrap()));
+                }
+            }
+            Ok(bindings)
+        }
+
+        /// Returns a scope which has the bindings returned by `get_stringified_bindings`.
+        pub fn from_stringified_bindings(bindings: &[(String, String)]) -> Scope {
+            let mut scope = Scope::new();
+            for (name, value) in bindings {
+                let parsed_value = JSON::parse(value).unwrap_or(JsValue::undefined());
+                scope = scope.with_binding(name, parsed_value);
+            }
+            scope
+        }
+    }
+
+    /// The parsed value of a `render-for` attribute, e.g. `{"(item, index) of state.list"}`.
+    #[derive(Debug, PartialEq)]
+    pub struct LoopHeader {
+        pub item: String,
+        pub index: Option<String>,
+        pub iterable: String,
+    }
+
     #[derive(Debug)]
     pub enum AttributeTextVariant {
         Boolean,
@@ -32,12 +136,13 @@ pub mod evaluator_mod {
             self,
             value: String,
             current_component: &Component,
+            scope: &Scope,
         ) -> Result<String, Error> {
             let attr_value;
             match self {
                 AttributeTextVariant::Expression => {
                     let attr_value_result =
-                        evaluate_expression_and_string(value, current_component);
+                        evaluate_expression_and_string(value, current_component, scope, false);
                     if attr_value_result.is_err() {
                         return Err(attr_value_result.unwrap_err());
                     }
@@ -86,21 +191,22 @@ pub mod evaluator_mod {
         Ok(result)
     }
 
-    /// Given a JS expression and context of the component which it was used in, returns a raw String
-    /// which is the evaluated result of the expression. In case of error, an `Err` variant is returned
-    /// which contains the reason.
-    fn evaluate_expression(
-        expression: String,
-        current_component: &Component,
-    ) -> Result<String, Error> {
-        let evaluator = get_state_props_evaluator(expression.to_owned());
-        let converted_state_result = to_value(current_component.get_state());
+    /// Given a JS expression, the stringified state and props it should see and the extra names bound
+    /// around it(loop variables, the event of an event handler, etc), returns the `JsValue` which the
+    /// expression evaluates to. In case of error, an `Err` variant is returned which contains the reason.
+    pub fn evaluate_expression_in_context(
+        expression: &String,
+        state: &String,
+        props: &String,
+        scope: &Scope,
+    ) -> Result<JsValue, Error> {
+        let converted_state_result = to_value(state);
         if converted_state_result.is_err() {
             return Err(Error::SerdeWasmBindgenError(
                 converted_state_result.unwrap_err(),
             ));
         }
-        let converted_prop_result = to_value(current_component.get_props());
+        let converted_prop_result = to_value(props);
         if converted_prop_result.is_err() {
             return Err(Error::SerdeWasmBindgenError(
                 converted_prop_result.unwrap_err(),
@@ -108,11 +214,22 @@ pub mod evaluator_mod {
         }
         let converted_prop = converted_prop_result.unwrap();
         let converted_state = converted_state_result.unwrap();
-        let expression_evaluation_result = evaluator.call2(
-            &JsValue::undefined(), // no value for `this` is provided to the evaluator.
-            &converted_state,
-            &converted_prop,
-        );
+        let expression_evaluation_result;
+        if scope.is_empty() {
+            let evaluator = get_state_props_evaluator(expression.to_owned());
+            expression_evaluation_result = evaluator.call2(
+                &JsValue::undefined(), // no value for `this` is provided to the evaluator.
+                &converted_state,
+                &converted_prop,
+            );
+        } else {
+            let evaluator = get_scoped_evaluator(expression.to_owned(), &scope.get_names());
+            let arguments = Array::of2(&converted_state, &converted_prop);
+            for value in scope.get_values() {
+                arguments.push(&value);
+            }
+            expression_evaluation_result = evaluator.apply(&JsValue::undefined(), &arguments);
+        }
         if expression_evaluation_result.is_err() {
             let msg: Result<String, serde_wasm_bindgen::Error> =
                 from_value(expression_evaluation_result.unwrap_err());
@@ -124,6 +241,38 @@ pub mod evaluator_mod {
                 "Failed to evaluate the following expression: {expression}: {msg}"
             )));
         }
+        Ok(expression_evaluation_result.unwrap())
+    }
+
+    /// Given a JS expression, context of the component which it was used in and the names bound by
+    /// `render-for` loops around it, returns the `JsValue` which the expression evaluates to. In case
+    /// of error, an `Err` variant is returned which contains the reason.
+    fn evaluate_expression_to_js_value(
+        expression: &String,
+        current_component: &Component,
+        scope: &Scope,
+    ) -> Result<JsValue, Error> {
+        evaluate_expression_in_context(
+            expression,
+            current_component.get_state(),
+            current_component.get_props(),
+            scope,
+        )
+    }
+
+    /// Given a JS expression, context of the component which it was used in and the names bound by
+    /// `render-for` loops around it, returns a raw String which is the evaluated result of the
+    /// expression. In case of error, an `Err` variant is returned which contains the reason.
+    fn evaluate_expression(
+        expression: String,
+        current_component: &Component,
+        scope: &Scope,
+    ) -> Result<String, Error> {
+        let expression_evaluation_result =
+            evaluate_expression_to_js_value(&expression, current_component, scope);
+        if expression_evaluation_result.is_err() {
+            return Err(expression_evaluation_result.unwrap_err());
+        }
         let evaluated_expression = expression_evaluation_result.unwrap();
         let evaluated_expression_string_result =
             fill_evaluated_expression_string_result(evaluated_expression, expression);
@@ -135,42 +284,36 @@ pub mod evaluator_mod {
 
     /// Given a String which contains a mix of JS expressions and strings plus the context of the component
     /// which it was used in, returns a raw String which is the evaluated result of the expression.
+    /// If `decode_strings` is true, character references in the strings, like `&amp;`, are decoded;
+    /// results of the expressions are never decoded.
     /// In case of error, an `Err` variant is returned which contains the reason.
     fn evaluate_expression_and_string(
         string_with_expression: String,
         current_component: &Component,
+        scope: &Scope,
+        decode_strings: bool,
     ) -> Result<String, Error> {
+        let parts_result = split_text_parts(&string_with_expression);
+        if parts_result.is_err() {
+            return Err(parts_result.unwrap_err());
+        }
         let mut result: String = String::new();
-        let mut current_expression: String = String::new();
-        let string_with_expression_chars: Vec<char> = string_with_expression.chars().collect();
-        let mut expression_stack = Vec::new();
-        for chr in string_with_expression_chars {
-            if is_input_open_curly_bracket(chr) {
-                expression_stack.push(OPEN_CURLY_BRACKET);
-            } else if is_input_close_curly_bracket(chr) {
-                let head = expression_stack.pop();
-                if head.is_none() {
-                    return Err(Error::ParsingError(format!("There was an error while parsing the following expression: {string_with_expression}. You have probably messed up some curly brackets.")));
+        for part in parts_result.unwrap() {
+            match part {
+                TextPart::String(string) => {
+                    if decode_strings {
+                        result += &decode_character_references(&string);
+                    } else {
+                        result += &string;
+                    }
                 }
-                if expression_stack.is_empty() {
+                TextPart::Expression(expression) => {
                     let evaluated_expression_result =
-                        evaluate_expression(current_expression, current_component);
+                        evaluate_expression(expression, current_component, scope);
                     if evaluated_expression_result.is_err() {
                         return Err(evaluated_expression_result.unwrap_err());
                     }
-                    let evaluated_expression = evaluated_expression_result.unwrap();
-                    result += &evaluated_expression;
-                    current_expression = "".to_owned();
-                } else {
-                    return Err(Error::_InvestigationNeeded(
-                        "Observe: when does this happen?".to_owned(),
-                    ));
-                }
-            } else {
-                if expression_stack.is_empty() {
-                    result += &chr.to_string();
-                } else {
-                    current_expression += &chr.to_string();
+                    result += &evaluated_expression_result.unwrap();
                 }
             }
         }
@@ -179,13 +322,22 @@ pub mod evaluator_mod {
 
     /// determines how should a value in attribute be treated. Returns an `Ok` variant which contains
     /// the value and its type; or `Err` variant with explanation if `text` does not follow the defined
-    /// attributes's value pattern.
+    /// attributes's value pattern. Values which are only wrapped in quotation marks, like `"btn"`, are
+    /// static strings; curly brackets inside them are not evaluated, but character references are decoded.
     fn get_attribute_text_variant(text: String) -> Result<TextInfo, Error> {
         let text_trimmed = text.trim();
+        if attribute_value_is_wrapped_in_quotes(text_trimmed) && text_trimmed.len() >= 2 {
+            let text_info = TextInfo {
+                value: decode_character_references(&text_trimmed[1..text_trimmed.len() - 1]),
+                variant: AttributeTextVariant::String,
+            };
+            return Ok(text_info);
+        }
         if is_a_valid_attribute_value(text_trimmed) {
             let inside_bracket = &text_trimmed[1..text_trimmed.len() - 1];
             let variant;
             let value;
+            let unescaped_inside_quotes;
             if attribute_value_is_number(inside_bracket) {
                 variant = AttributeTextVariant::Number;
                 value = inside_bracket;
@@ -194,6 +346,7 @@ pub mod evaluator_mod {
                 value = inside_bracket;
             } else if attribute_value_is_wrapped_in_quotes(inside_bracket) {
                 let inside_quotes = &inside_bracket[1..inside_bracket.len() - 1];
+                unescaped_inside_quotes = unescape_curly_brackets(inside_quotes);
                 let inside_quotes_has_valid_expression =
                     has_valid_expression_inside(inside_quotes.to_owned());
                 if inside_quotes_has_valid_expression {
@@ -201,7 +354,7 @@ pub mod evaluator_mod {
                     value = inside_quotes;
                 } else {
                     variant = AttributeTextVariant::String;
-                    value = inside_quotes;
+                    value = unescaped_inside_quotes.as_str();
                 }
             } else {
                 variant = AttributeTextVariant::Expression;
@@ -222,32 +375,235 @@ pub mod evaluator_mod {
     pub fn evaluate_attribute_value_to_raw_string(
         value: String,
         current_component: &Component,
+        scope: &Scope,
     ) -> Result<String, Error> {
         let attribute_value_variant_result = get_attribute_text_variant(value.to_owned());
         if attribute_value_variant_result.is_err() {
             return Err(attribute_value_variant_result.unwrap_err());
         }
         let TextInfo { value, variant } = attribute_value_variant_result.unwrap();
-        let attr_value_result = variant.get_variant_as_string(value, current_component);
+        let attr_value_result = variant.get_variant_as_string(value, current_component, scope);
         if attr_value_result.is_err() {
             return Err(attr_value_result.unwrap_err());
         }
         Ok(attr_value_result.unwrap())
     }
 
-    /// Evaluates the given text value in the context of provided component.
+    /// Evaluates the given attribute value of a component tag in the context of provided component.
+    /// Unlike `evaluate_attribute_value_to_raw_string`, the result keeps its JS type, so objects and
+    /// arrays can be passed down as props; it is returned as a JSON string. `None` is returned if the
+    /// value is `undefined` or a function, which JSON can't represent.
+    pub fn evaluate_attribute_value_to_json(
+        value: String,
+        current_component: &Component,
+        scope: &Scope,
+    ) -> Result<Option<String>, Error> {
+        let value_trimmed = value.trim();
+        let evaluated_value;
+        if attribute_value_is_wrapped_in_quotes(value_trimmed) && value_trimmed.len() >= 2 {
+            evaluated_value = JsValue::from_str(&decode_character_references(
+                &value_trimmed[1..value_trimmed.len() - 1],
+            ));
+        } else if !is_a_valid_attribute_value(value_trimmed) {
+            return Err(Error::ParsingError(format!(
+                "The following text value didn't have any of the supported types: {value}"
+            )));
+        } else if attribute_value_is_wrapped_in_quotes(
+            value_trimmed[1..value_trimmed.len() - 1].trim(),
+        ) {
+            let string_value_result =
+                evaluate_attribute_value_to_raw_string(value.to_owned(), current_component, scope);
+            if string_value_result.is_err() {
+                return Err(string_value_result.unwrap_err());
+            }
+            evaluated_value = JsValue::from_str(&string_value_result.unwrap());
+        } else {
+            let inside_bracket = value_trimmed[1..value_trimmed.len() - 1].trim();
+            let evaluated_value_result = evaluate_expression_to_js_value(
+                &inside_bracket.to_owned(),
+                current_component,
+                scope,
+            );
+            if evaluated_value_result.is_err() {
+                return Err(evaluated_value_result.unwrap_err());
+            }
+            evaluated_value = evaluated_value_result.unwrap();
+        }
+        if evaluated_value.is_undefined() {
+            return Ok(None);
+        }
+        let stringified_value = JSON::stringify(&evaluated_value);
+        if stringified_value.is_err() {
+            return Err(Error::TypeError(format!(
+                "The following value couldn't be converted to JSON: {value}"
+            )));
+        }
+        Ok(stringified_value.unwrap().as_string())
+    }
+
+    /// Returns the JS expression inside the curly brackets of an attribute value, without evaluating
+    /// it. Returns an `Err` variant if `value` is not wrapped inside curly brackets.
+    pub fn get_attribute_expression(value: &String) -> Result<String, Error> {
+        let value_trimmed = value.trim();
+        if !is_a_valid_attribute_value(value_trimmed) {
+            return Err(Error::ParsingError(format!(
+                "The following text value didn't have any of the supported types: {value}"
+            )));
+        }
+        Ok(value_trimmed[1..value_trimmed.len() - 1].trim().to_owned())
+    }
+
+    /// Evaluates the given text value in the context of provided component. `{{` and `}}` stand for
+    /// literal curly brackets. Character references in the text, like `&lt;` or `&#123;`, are decoded,
+    /// so they can't start an expression.
     pub fn evaluate_text_value_to_raw_string(
         text: &String,
         current_component: &Component,
+        scope: &Scope,
     ) -> Result<String, Error> {
         let has_valid_exp = has_valid_expression_inside(text.to_owned());
         if has_valid_exp {
-            return evaluate_expression_and_string(text.to_owned(), current_component);
+            return evaluate_expression_and_string(text.to_owned(), current_component, scope, true);
         } else {
-            return Ok(text.to_owned());
+            return Ok(decode_character_references(&unescape_curly_brackets(text)));
         }
     }
 
+    /// Returns the error which is used when the value of a `render-for` attribute is malformed.
+    fn get_loop_header_error(value: &str) -> Error {
+        Error::ParsingError(format!(
+            "The value of `{RENDER_FOR_ATTRIBUTE_NAME}` must look like {{\"item of expression\"}} or {{\"(item, index) of expression\"}}, but it was: {value}"
+        ))
+    }
+
+    /// Returns an `Err` variant if `name` can't be used as the name of a loop variable, i.e. it is
+    /// not a plain JS identifier or it is already used by the evaluator.
+    fn validate_loop_variable(name: &str, value: &str) -> Result<(), Error> {
+        if is_input_reserved_parameter(name) {
+            return Err(Error::ParsingError(format!(
+                "`{name}` can't be used as a loop variable in the following `{RENDER_FOR_ATTRIBUTE_NAME}`: {value}"
+            )));
+        }
+        if !is_valid_identifier(name) {
+            return Err(get_loop_header_error(value));
+        }
+        Ok(())
+    }
+
+    /// Returns an `Err` variant if `name`, the evaluated value of a `slot-scope` attribute, can't be
+    /// used as the name which the values of a scoped slot are bound to.
+    pub fn validate_slot_scope(name: &str) -> Result<(), Error> {
+        if is_input_reserved_parameter(name) || !is_valid_identifier(name) {
+            return Err(Error::ParsingError(format!(
+                "The value of `{SLOT_SCOPE_ATTRIBUTE_NAME}` must be a JS identifier other than the ones used by the evaluator, but it was: {name}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Parses the value of a `render-for` attribute. Both `{"item of state.list"}` and
+    /// `{"(item, index) of state.list"}` are accepted; the expression after `of` may be any JS
+    /// expression which evaluates to an array.
+    pub fn parse_loop_header(value: &str) -> Result<LoopHeader, Error> {
+        let value_trimmed = value.trim();
+        if !is_a_valid_attribute_value(value_trimmed) {
+            return Err(get_loop_header_error(value));
+        }
+        let inside_bracket = value_trimmed[1..value_trimmed.len() - 1].trim();
+        if !attribute_value_is_wrapped_in_quotes(inside_bracket) || inside_bracket.len() < 2 {
+            return Err(get_loop_header_error(value));
+        }
+        let inside_quotes = &inside_bracket[1..inside_bracket.len() - 1];
+        let split = inside_quotes.split_once(LOOP_SEPARATOR);
+        if split.is_none() {
+            return Err(get_loop_header_error(value));
+        }
+        let (variables, iterable) = split.unwrap();
+        let variables = variables.trim();
+        let iterable = iterable.trim();
+        if iterable.is_empty() {
+            return Err(get_loop_header_error(value));
+        }
+
+        let item;
+        let index;
+        if variables.starts_with('(') && variables.ends_with(')') {
+            let inside_parentheses = &variables[1..variables.len() - 1];
+            let names: Vec<&str> = inside_parentheses
+                .split(',')
+                .map(|name| name.trim())
+                .collect();
+            if names.len() != 2 {
+                return Err(get_loop_header_error(value));
+            }
+            item = names[0].to_owned();
+            index = Some(names[1].to_owned());
+        } else {
+            item = variables.to_owned();
+            index = None;
+        }
+
+        let item_validation_result = validate_loop_variable(&item, value);
+        if item_validation_result.is_err() {
+            return Err(item_validation_result.unwrap_err());
+        }
+        if index.is_some() {
+            let index_name = index.as_ref().unwrap();
+            let index_validation_result = validate_loop_variable(index_name, value);
+            if index_validation_result.is_err() {
+                return Err(index_validation_result.unwrap_err());
+            }
+            if *index_name == item {
+                return Err(get_loop_header_error(value));
+            }
+        }
+        Ok(LoopHeader {
+            item,
+            index,
+            iterable: iterable.to_owned(),
+        })
+    }
+
+    /// Evaluates the value of a `render-for` attribute in the context of the provided component and
+    /// returns one scope per element of the iterated array. Each of them extends `scope` with the
+    /// loop variable, and the index variable if there is one. Returns an `Err` variant if the value
+    /// is malformed or the expression doesn't evaluate to an array.
+    pub fn evaluate_loop(
+        value: &str,
+        current_component: &Component,
+        scope: &Scope,
+    ) -> Result<Vec<Scope>, Error> {
+        let loop_header_result = parse_loop_header(value);
+        if loop_header_result.is_err() {
+            return Err(loop_header_result.unwrap_err());
+        }
+        let LoopHeader {
+            item,
+            index,
+            iterable,
+        } = loop_header_result.unwrap();
+        let iterable_result = evaluate_expression_to_js_value(&iterable, current_component, scope);
+        if iterable_result.is_err() {
+            return Err(iterable_result.unwrap_err());
+        }
+        let iterable_value = iterable_result.unwrap();
+        if !Array::is_array(&iterable_value) {
+            return Err(Error::EvaluationError(format!(
+                "The following `{RENDER_FOR_ATTRIBUTE_NAME}` expression didn't evaluate to an array: {iterable}"
+            )));
+        }
+        let mut scopes: Vec<Scope> = Vec::new();
+        for (position, element) in Array::from(&iterable_value).iter().enumerate() {
+            let mut item_scope = scope.with_binding(&item, element);
+            if index.is_some() {
+                item_scope = item_scope
+                    .with_binding(index.as_ref().unwrap(), JsValue::from_f64(position as f64));
+            }
+            scopes.push(item_scope);
+        }
+        Ok(scopes)
+    }
+
     #[cfg(test)]
     mod tests {
         use wasm_bindgen::JsValue;
@@ -330,6 +686,108 @@ pub mod evaluator_mod {
             )
         }
 
+        #[test]
+        /// Values which are only wrapped in quotation marks are static strings, even if they contain
+        /// curly brackets; values wrapped in curly brackets keep their previous variants.
+        fn test_get_attribute_text_variant_static() {
+            let text_info_result = get_attribute_text_variant("'btn {state.kind}'".to_owned());
+            assert!(matches!(text_info_result, Ok(TextInfo {
+                variant: AttributeTextVariant::String,
+                value
+            }) if value == "btn {state.kind}"));
+            let text_info_result = get_attribute_text_variant("\"\"".to_owned());
+            assert!(matches!(text_info_result, Ok(TextInfo {
+                variant: AttributeTextVariant::String,
+                value
+            }) if value.is_empty()));
+            let text_info_result = get_attribute_text_variant("{true}".to_owned());
+            assert!(matches!(
+                text_info_result,
+                Ok(TextInfo {
+                    variant: AttributeTextVariant::Boolean,
+                    ..
+                })
+            ));
+        }
+
+        #[test]
+        /// `parse_loop_header` must accept a loop variable with an optional index.
+        fn test_parse_loop_header() {
+            let header_result = parse_loop_header("{\"item of state.list\"}");
+            assert!(matches!(header_result, Ok(header) if header == LoopHeader {
+                item: "item".to_owned(),
+                index: None,
+                iterable: "state.list".to_owned()
+            }));
+            let header_result = parse_loop_header("{'(row, i) of state.rows.filter(r => r.ok)'}");
+            assert!(matches!(header_result, Ok(header) if header == LoopHeader {
+                item: "row".to_owned(),
+                index: Some("i".to_owned()),
+                iterable: "state.rows.filter(r => r.ok)".to_owned()
+            }));
+        }
+
+        #[test]
+        /// `parse_loop_header` must reject malformed values and loop variables which would shadow
+        /// the parameters of the evaluator.
+        fn test_parse_loop_header_invalid() {
+            let invalid_values = vec![
+                "{state.list}",
+                "{\"item in state.list\"}",
+                "{\"item of \"}",
+                "{\"(a, b, c) of state.list\"}",
+                "{\"(a, a) of state.list\"}",
+                "{\"1item of state.list\"}",
+                "{\"state of state.list\"}",
+            ];
+            for value in invalid_values {
+                assert!(matches!(
+                    parse_loop_header(value),
+                    Err(Error::ParsingError(_))
+                ));
+            }
+        }
+
+        #[test]
+        /// `validate_slot_scope` must only accept identifiers which don't shadow the parameters of the
+        /// evaluator.
+        fn test_validate_slot_scope() {
+            assert!(validate_slot_scope("row").is_ok());
+            for name in ["", "1row", "my-row", "props", "state"] {
+                assert!(matches!(
+                    validate_slot_scope(name),
+                    Err(Error::ParsingError(_))
+                ));
+            }
+        }
+
+        #[wasm_bindgen_test]
+        /// Bindings of a `Scope` must shadow the ones with the same name, and keep their order
+        /// otherwise.
+        fn test_scope_with_binding() {
+            let scope = Scope::new()
+                .with_binding("row", JsValue::from_f64(1.0))
+                .with_binding("cell", JsValue::from_f64(2.0))
+                .with_binding("row", JsValue::from_f64(3.0));
+            assert_eq!(scope.get_names(), vec!["cell".to_owned(), "row".to_owned()]);
+            assert_eq!(scope.get_values()[1].as_f64(), Some(3.0));
+        }
+
+        #[wasm_bindgen_test]
+        /// Stringified bindings must turn back into the same bindings, leaving out `undefined`.
+        fn test_scope_stringified_bindings() {
+            let scope = Scope::new()
+                .with_binding("row", JsValue::from_str("a"))
+                .with_binding("missing", JsValue::undefined());
+            let bindings_result = scope.get_stringified_bindings();
+            assert!(
+                matches!(&bindings_result, Ok(bindings) if *bindings == vec![("row".to_owned(), "\"a\"".to_owned())])
+            );
+            let restored = Scope::from_stringified_bindings(&bindings_result.unwrap());
+            assert_eq!(restored.get_names(), vec!["row".to_owned()]);
+            assert_eq!(restored.get_values()[0].as_string(), Some("a".to_owned()));
+        }
+
         #[wasm_bindgen_test]
         /// `fill_evaluated_expression_string_result` must return error when provided JsValue is not of type
         /// `string`, `number`, `bool`, `undefined` or `null`.
@@ -353,7 +811,7 @@ pub mod evaluator_mod {
     // won't be considered                5- constant values defined higher in the scope -> not gonna happen
     // supported/    supported            6- primitive data types like string, number and boolean -> they'll be treated like expressions: new Function syntax
     // supported/    supported            7- using operators to render jsx content conditionally
-    // supported/not supported            8- using map to render a list of data
+    // supported/    supported            8- using map to render a list of data -> `render-for` attribute
 
     // handling 1:
     // although this can be easily replaced with the addEventListener functionality, we can't ignore the
@@ -1,13 +1,112 @@
 /// Contains utility functions used in the process of evaluation
 pub mod evaluator_util {
-    use crate::const_util::const_util_mod::{CLOSE_CURLY_BRACKET, OPEN_CURLY_BRACKET};
+    use crate::{
+        const_util::const_util_mod::{
+            get_named_character_reference, is_input_close_curly_bracket,
+            is_input_open_curly_bracket, CLOSE_CURLY_BRACKET, OPEN_CURLY_BRACKET,
+        },
+        error::error_mod::Error,
+    };
 
-    /// performs a look ahead search on the validity of JS expression. This requires using
-    /// escape chars for special chars.
+    /// A part of a text which contains a mix of strings and JS expressions, like `hi {state.name}`.
+    /// Escaped curly brackets are already unescaped in `String` parts.
+    #[derive(Debug, PartialEq)]
+    pub enum TextPart {
+        String(String),
+        Expression(String),
+    }
+
+    /// Returns true if the char at `index` of `chars` is a curly bracket which is escaped by doubling it,
+    /// i.e. `{{` or `}}`; in that case, the bracket at `index + 1` is a part of the escape.
+    fn is_escaped_curly_bracket(chars: &[char], index: usize) -> bool {
+        let current = chars[index];
+        (is_input_open_curly_bracket(current) || is_input_close_curly_bracket(current))
+            && chars.get(index + 1) == Some(&current)
+    }
+
+    /// performs a look ahead search on the validity of JS expression. Curly brackets which are escaped
+    /// as `{{` or `}}` outside of expressions are ignored, so `{{state}}` is not an expression.
     pub fn has_valid_expression_inside(text: String) -> bool {
-        let open_c = text.matches(OPEN_CURLY_BRACKET).count();
-        let close_c = text.matches(CLOSE_CURLY_BRACKET).count();
-        return open_c == close_c && open_c > 0;
+        let chars: Vec<char> = text.chars().collect();
+        let mut depth = 0usize;
+        let mut has_expression = false;
+        let mut index = 0usize;
+        while index < chars.len() {
+            if depth == 0 && is_escaped_curly_bracket(&chars, index) {
+                index += 2;
+                continue;
+            }
+            if is_input_open_curly_bracket(chars[index]) {
+                depth += 1;
+                has_expression = true;
+            } else if is_input_close_curly_bracket(chars[index]) {
+                if depth == 0 {
+                    return false;
+                }
+                depth -= 1;
+            }
+            index += 1;
+        }
+        has_expression && depth == 0
+    }
+
+    /// Replaces escaped curly brackets, `{{` and `}}`, with single ones.
+    pub fn unescape_curly_brackets(text: &str) -> String {
+        text.replace("{{", "{").replace("}}", "}")
+    }
+
+    /// Splits a text which contains a mix of strings and JS expressions into its parts. `{{` and `}}`
+    /// outside of expressions are escapes for literal curly brackets. Returns an `Err` variant if
+    /// curly brackets are not balanced.
+    pub fn split_text_parts(text: &str) -> Result<Vec<TextPart>, Error> {
+        let get_error = || {
+            Error::ParsingError(format!("There was an error while parsing the following expression: {text}. You have probably messed up some curly brackets."))
+        };
+        let chars: Vec<char> = text.chars().collect();
+        let mut parts: Vec<TextPart> = Vec::new();
+        let mut current_string = String::new();
+        let mut current_expression = String::new();
+        let mut expression_stack = Vec::new();
+        let mut index = 0usize;
+        while index < chars.len() {
+            let chr = chars[index];
+            if expression_stack.is_empty() && is_escaped_curly_bracket(&chars, index) {
+                current_string.push(chr);
+                index += 2;
+                continue;
+            }
+            if is_input_open_curly_bracket(chr) {
+                if !current_string.is_empty() {
+                    parts.push(TextPart::String(current_string));
+                    current_string = String::new();
+                }
+                expression_stack.push(OPEN_CURLY_BRACKET);
+            } else if is_input_close_curly_bracket(chr) {
+                let head = expression_stack.pop();
+                if head.is_none() {
+                    return Err(get_error());
+                }
+                if !expression_stack.is_empty() {
+                    return Err(Error::_InvestigationNeeded(
+                        "Observe: when does this happen?".to_owned(),
+                    ));
+                }
+                parts.push(TextPart::Expression(current_expression));
+                current_expression = String::new();
+            } else if expression_stack.is_empty() {
+                current_string.push(chr);
+            } else {
+                current_expression.push(chr);
+            }
+            index += 1;
+        }
+        if !expression_stack.is_empty() {
+            return Err(get_error());
+        }
+        if !current_string.is_empty() {
+            parts.push(TextPart::String(current_string));
+        }
+        Ok(parts)
     }
 
     /// Given a trimmed string input, checks whether it is wrapped inside curly brackets or not.
@@ -25,12 +124,75 @@ pub mod evaluator_util {
         text.parse::<bool>().is_ok()
     }
 
+    /// Returns true if `text` can be used as a plain JS identifier, e.g. as a parameter name.
+    pub fn is_valid_identifier(text: &str) -> bool {
+        let mut chars = text.chars();
+        let first = chars.next();
+        if first.is_none() {
+            return false;
+        }
+        let first = first.unwrap();
+        let is_identifier_char = |chr: char| chr.is_alphanumeric() || chr == '_' || chr == '$';
+        !first.is_numeric() && is_identifier_char(first) && chars.all(is_identifier_char)
+    }
+
     /// Returns true if `text` is wrapped inside quotation marks.
     pub fn attribute_value_is_wrapped_in_quotes(text: &str) -> bool {
         (text.starts_with("\"") && text.ends_with("\""))
             || (text.starts_with("'") && text.ends_with("'"))
     }
 
+    /// Given a text which starts with `&`, returns the char which the character reference at its start
+    /// stands for, along with the length of that reference in bytes; `None` is returned if there is no
+    /// valid reference there. Numeric references which don't refer to a valid char stand for U+FFFD.
+    fn decode_character_reference(text: &str) -> Option<(char, usize)> {
+        let end = text.find(';')?;
+        let name = &text[1..end];
+        let decoded;
+        if let Some(number) = name.strip_prefix('#') {
+            let code;
+            if let Some(hex) = number.strip_prefix(['x', 'X']) {
+                if hex.is_empty() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return None;
+                }
+                code = u32::from_str_radix(hex, 16).unwrap_or(u32::MAX);
+            } else {
+                if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()) {
+                    return None;
+                }
+                code = number.parse::<u32>().unwrap_or(u32::MAX);
+            }
+            decoded = char::from_u32(code)
+                .filter(|c| *c != '\0')
+                .unwrap_or(char::REPLACEMENT_CHARACTER);
+        } else {
+            decoded = get_named_character_reference(name)?;
+        }
+        Some((decoded, end + 1))
+    }
+
+    /// Replaces named and numeric character references in `text`, like `&amp;`, `&#123;` or `&#x1F600;`,
+    /// with the chars they stand for. Ampersands which don't start a known reference are kept as they are.
+    pub fn decode_character_references(text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find('&') {
+            result.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let decoded = decode_character_reference(rest);
+            if decoded.is_some() {
+                let (chr, length) = decoded.unwrap();
+                result.push(chr);
+                rest = &rest[length..];
+            } else {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+        result.push_str(rest);
+        result
+    }
+
     #[cfg(test)]
     mod tests {
         use crate::evaluator::util::evaluator_util::*;
@@ -54,12 +216,46 @@ pub mod evaluator_util {
         }
 
         #[test]
-        #[ignore = "This method of escaping chars is not helpful. We need another way but this ain't crucial."]
         /// `has_valid_expression_inside` should ignore escaped curly brackets.
         fn test_no_valid_expression_inside_with_curly_brackets() {
-            let expression = String::from("some text with arbitrary \u{007D} and \u{007B}");
+            let expression = String::from("some text with arbitrary }} and {{");
             let has_valid_expression_inside_result = has_valid_expression_inside(expression);
             assert!(!has_valid_expression_inside_result);
+            let expression = String::from("function f() {{ return {state.x}; }}");
+            let has_valid_expression_inside_result = has_valid_expression_inside(expression);
+            assert!(has_valid_expression_inside_result);
+        }
+
+        #[test]
+        /// Escaped curly brackets must become literal ones, next to real expressions.
+        fn test_split_text_parts() {
+            let parts_result = split_text_parts("{{ {state.a} }}{{{state.b}}}x");
+            assert!(matches!(parts_result, Ok(parts) if parts == vec![
+                TextPart::String("{ ".to_owned()),
+                TextPart::Expression("state.a".to_owned()),
+                TextPart::String(" }{".to_owned()),
+                TextPart::Expression("state.b".to_owned()),
+                TextPart::String("}x".to_owned()),
+            ]));
+        }
+
+        #[test]
+        /// Unbalanced curly brackets must be reported by `split_text_parts`.
+        fn test_split_text_parts_invalid() {
+            assert!(matches!(
+                split_text_parts("a } {state.a}"),
+                Err(Error::ParsingError(_))
+            ));
+            assert!(matches!(
+                split_text_parts("a {state.a"),
+                Err(Error::ParsingError(_))
+            ));
+        }
+
+        #[test]
+        /// `unescape_curly_brackets` must turn each pair of brackets into a single one.
+        fn test_unescape_curly_brackets() {
+            assert_eq!(unescape_curly_brackets("{{{a}}} {b}"), "{{a}} {b}");
         }
 
         #[test]
@@ -100,6 +296,24 @@ pub mod evaluator_util {
             assert!(!attribute_value_is_bool("False"));
         }
 
+        #[test]
+        /// `decode_character_references` must decode named, decimal and hexadecimal references.
+        fn test_decode_character_references() {
+            let decoded =
+                decode_character_references("a &amp; b &lt;p&gt; &#123;x&#x7D;&nbsp;&#x1F600;");
+            assert_eq!(decoded, "a & b <p> {x}\u{a0}\u{1F600}");
+        }
+
+        #[test]
+        /// Unknown or malformed references must be kept as they are, while numeric references to
+        /// invalid chars must become U+FFFD.
+        fn test_decode_character_references_invalid() {
+            let decoded = decode_character_references("R&D &foo; &amp &#; &#xZ; & ;");
+            assert_eq!(decoded, "R&D &foo; &amp &#; &#xZ; & ;");
+            let decoded = decode_character_references("&#0;&#xD800;&#99999999999;");
+            assert_eq!(decoded, "\u{FFFD}\u{FFFD}\u{FFFD}");
+        }
+
         #[test]
         /// `attribute_value_is_wrapped_in_quotes` must return true when it is wrapped between allowed
         /// quotation marks, i.e., `"` and `'`.
@@ -113,5 +327,15 @@ pub mod evaluator_util {
         fn test_attribute_value_is_not_wrapped_in_quotes() {
             assert!(!attribute_value_is_wrapped_in_quotes("\"hi:('"));
         }
+
+        #[test]
+        /// `is_valid_identifier` must only accept names which can be used as JS parameters.
+        fn test_is_valid_identifier() {
+            assert!(is_valid_identifier("item"));
+            assert!(is_valid_identifier("_$row2"));
+            assert!(!is_valid_identifier("2row"));
+            assert!(!is_valid_identifier("row.cell"));
+            assert!(!is_valid_identifier(""));
+        }
     }
 }
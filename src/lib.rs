@@ -1,9 +1,10 @@
 mod component;
 mod const_util;
+pub mod diff;
 mod dom;
 mod error;
 mod evaluator;
 mod parser;
 mod presenter;
-mod tokenizer;
+pub mod tokenizer;
 pub mod lib_mod {}
@@ -1,13 +1,19 @@
+mod whitespace;
 pub mod parser_mod {
 
+    use super::whitespace::whitespace_mod::collapse_whitespace;
     use crate::component::component_mod::Component;
-    use crate::const_util::const_util_mod::ATTRIBUTE_KEY_VALUE_SEPARATOR;
-    use crate::error::error_mod::Error as CustomError;
+    use crate::const_util::const_util_mod::{
+        is_input_raw_text_element, is_input_void_element, is_input_whitespace_preserving_element,
+        BOOLEAN_ATTRIBUTE_VALUE, FRAGMENT_TAG_NAME, SLOT_TAG_NAME,
+    };
+    use crate::error::error_mod::{locate_error, Diagnostic, Error as CustomError, Severity};
     use crate::presenter::presenter_mod::ParsedPresenter;
-    use crate::tokenizer::tokenizer_mod::{tokenizer, CurrentState, TokenizerState};
+    use crate::tokenizer::tokenizer_mod::{Span, Token, TokenKind, Tokenizer};
     use serde::{Deserialize, Serialize};
     use serde_wasm_bindgen::from_value;
     use std::collections::HashMap;
+    use std::sync::atomic::{AtomicBool, Ordering};
     use wasm_bindgen::prelude::*;
     use wasm_bindgen_futures::JsFuture;
     use web_sys::js_sys::Promise;
@@ -17,13 +23,48 @@ pub mod parser_mod {
         Component(Component), //component object
         Tag(String),          // tag name
         Text(String),         // text content
+        Comment(String), // comment content; only kept if `set_keep_comments` was called with true
+        Fragment,        // only its children are rendered
+        // content for a scoped slot, along with the component which passed it and the loop variables
+        // around it as JSON strings; its children are evaluated by the slot which renders it.
+        ScopedSlot(Component, Vec<(String, String)>),
     }
 
+    /// `span` is where the node is in the presenter of the component it was used in, from its opening
+    /// tag to its closing one, and `attribute_spans` holds the span of each of its attributes. Both are
+    /// only used to point errors at the right place; nodes which weren't parsed have default spans.
     #[derive(Serialize, Deserialize, Debug, Clone)]
     pub struct VirtualNode {
         pub node_type: NodeType,
         pub attributes: HashMap<String, String>,
         pub children: Vec<VirtualNode>,
+        #[serde(default)]
+        pub span: Span,
+        #[serde(default)]
+        pub attribute_spans: HashMap<String, Span>,
+    }
+
+    /// Whether comments of presenters become `Comment` nodes, rather than being dropped.
+    static KEEP_COMMENTS: AtomicBool = AtomicBool::new(false);
+
+    /// Changes whether comments of presenters are kept in the vdom as `Comment` nodes. Only presenters
+    /// which are parsed afterwards are affected.
+    pub fn set_keep_comments(keep: bool) {
+        KEEP_COMMENTS.store(keep, Ordering::Relaxed);
+    }
+
+    /// Whether presenters are parsed with `parse_vdom_from_string_recovering`, rather than stopping at
+    /// the first error.
+    static RECOVER_FROM_ERRORS: AtomicBool = AtomicBool::new(false);
+
+    /// Changes whether presenters which are parsed afterwards are parsed in the recovering mode.
+    pub fn set_recover_from_errors(recover: bool) {
+        RECOVER_FROM_ERRORS.store(recover, Ordering::Relaxed);
+    }
+
+    /// Returns whether presenters are parsed in the recovering mode; see `set_recover_from_errors`.
+    pub fn is_recovering_from_errors() -> bool {
+        RECOVER_FROM_ERRORS.load(Ordering::Relaxed)
     }
 
     // This path should be kept in sync with where the specified file actually resides.
@@ -59,120 +100,824 @@ pub mod parser_mod {
         Ok(component)
     }
 
-    /// The final stack which contains the info of VDOM, should only have one item in the end; which
-    /// in this case, an `Ok` variant containing a VirtualNode is returned. Other than that an `Err`
-    /// variant is returned explaining the reason.
-    fn get_parser_return_value(stack: Vec<VirtualNode>) -> Result<VirtualNode, CustomError> {
+    /// The final stack which contains the info of VDOM holds the top-level nodes of the presenter. If
+    /// there is only one of them, an `Ok` variant containing it is returned; if there are more, they are
+    /// wrapped inside a `Fragment` node. An `Err` variant is returned if the presenter has no nodes.
+    fn get_parser_return_value(mut stack: Vec<VirtualNode>) -> Result<VirtualNode, CustomError> {
         if stack.len() == 1 {
-            let top = stack.get(0).unwrap();
-            return Ok(top.clone());
+            return Ok(stack.pop().unwrap());
+        }
+        if stack.is_empty() {
+            let msg = "Presenter of each component must have at least one node.".to_owned();
+            return Err(CustomError::ParsingError(msg));
+        }
+        let span = Span {
+            end: stack.last().unwrap().span.end,
+            ..stack[0].span
+        };
+        Ok(VirtualNode {
+            node_type: NodeType::Fragment,
+            attributes: HashMap::new(),
+            children: stack,
+            span,
+            attribute_spans: HashMap::new(),
+        })
+    }
+
+    /// Returns a fragment without children, which renders nothing.
+    fn get_empty_fragment() -> VirtualNode {
+        VirtualNode {
+            node_type: NodeType::Fragment,
+            attributes: HashMap::new(),
+            children: Vec::new(),
+            span: Span::default(),
+            attribute_spans: HashMap::new(),
+        }
+    }
+
+    /// Returns the name which closes `node` in the markup. Components don't keep the name they were
+    /// imported as, so it is read from their opening tag in `markup`. While recovering, a fragment may
+    /// stand in for a component which couldn't be resolved, and is then closed by its name as well.
+    fn get_tag_name(node: &VirtualNode, markup: &str) -> String {
+        let name_in_markup: String = markup
+            .get(node.span.start + 1..)
+            .unwrap_or_default()
+            .trim_start()
+            .chars()
+            .take_while(|c| c.is_alphanumeric())
+            .collect();
+        match &node.node_type {
+            NodeType::Tag(name) => name.to_owned(),
+            NodeType::Component(_) => name_in_markup,
+            NodeType::Text(_) => "text".to_owned(),
+            NodeType::Comment(_) => "comment".to_owned(),
+            NodeType::Fragment if name_in_markup.starts_with(char::is_uppercase) => name_in_markup,
+            NodeType::Fragment => FRAGMENT_TAG_NAME.to_owned(),
+            NodeType::ScopedSlot(..) => SLOT_TAG_NAME.to_owned(),
+        }
+    }
+
+    /// Pops the innermost open node off `stack`, given that `closing_name` is the name of the closing
+    /// tag which was just read, along with its `span`. An `Err` variant naming both tags is returned
+    /// if the closing tag doesn't match the innermost open one, or if there is no open tag at all.
+    fn close_node(
+        stack: &mut Vec<VirtualNode>,
+        closing_name: &str,
+        span: &Span,
+        markup: &str,
+    ) -> Result<VirtualNode, CustomError> {
+        if is_input_void_element(closing_name) {
+            let msg = format!("`<{closing_name}>` is a void element, so it can't have children or a closing tag; remove `</{closing_name}>`.");
+            return Err(locate_error(CustomError::ParsingError(msg), span, markup));
+        }
+        let open_node = stack.last();
+        if open_node.is_none() {
+            let msg = format!(
+                "Found `</{closing_name}>`, but there is no open `<{closing_name}>` to close."
+            );
+            return Err(locate_error(CustomError::ParsingError(msg), span, markup));
+        }
+        let open_node = open_node.unwrap();
+        let open_name = get_tag_name(open_node, markup);
+        if open_name != closing_name {
+            let msg = format!(
+                "Expected `</{open_name}>` to close `<{open_name}>` (line {}, column {}), but found `</{closing_name}>`.",
+                open_node.span.line, open_node.span.column
+            );
+            return Err(locate_error(CustomError::ParsingError(msg), span, markup));
+        }
+        let mut completed_node = stack.pop().unwrap();
+        completed_node.span.end = span.end;
+        Ok(completed_node)
+    }
+
+    /// Returns the error for a node which was still open when the markup ended.
+    fn get_unclosed_node_error(node: &VirtualNode, markup: &str) -> CustomError {
+        let name = get_tag_name(node, markup);
+        let msg = format!(
+            "`<{name}>` was never closed; expected a `</{name}>` before the end of the presenter."
+        );
+        locate_error(CustomError::ParsingError(msg), &node.span, markup)
+    }
+
+    /// Records `error` if `diagnostics` is `Some`, i.e. the parser is recovering from errors;
+    /// otherwise, an `Err` variant containing it is returned, so that parsing stops.
+    fn report(
+        error: CustomError,
+        severity: Severity,
+        diagnostics: &mut Option<&mut Vec<Diagnostic>>,
+    ) -> Result<(), CustomError> {
+        if diagnostics.is_none() {
+            return Err(error);
+        }
+        diagnostics
+            .as_mut()
+            .unwrap()
+            .push(Diagnostic { severity, error });
+        Ok(())
+    }
+
+    /// Recovers from a closing tag named `closing_name`, which `close_node` couldn't match, given that
+    /// `span` is where it is. If an open node deeper in `stack` has that name, the nodes inside it are
+    /// closed right before the closing tag, and so is the node itself; otherwise, the closing tag is
+    /// ignored.
+    fn close_node_recovering(
+        stack: &mut Vec<VirtualNode>,
+        vdom: &mut Vec<VirtualNode>,
+        closing_name: &str,
+        span: &Span,
+        markup: &str,
+    ) {
+        let unclosed_count = stack
+            .iter()
+            .rev()
+            .position(|node| get_tag_name(node, markup) == closing_name);
+        if unclosed_count.is_none() {
+            return;
+        }
+        for _ in 0..unclosed_count.unwrap() {
+            let mut unclosed_node = stack.pop().unwrap();
+            unclosed_node.span.end = span.start;
+            push_completed_node(stack, vdom, unclosed_node);
+        }
+        let mut completed_node = stack.pop().unwrap();
+        completed_node.span.end = span.end;
+        push_completed_node(stack, vdom, completed_node);
+    }
+
+    /// Adds a node which is complete, i.e. closed, to the children of the innermost open node; or to
+    /// `vdom` if there is no open node.
+    fn push_completed_node(
+        stack: &mut Vec<VirtualNode>,
+        vdom: &mut Vec<VirtualNode>,
+        node: VirtualNode,
+    ) {
+        let parent_node = stack.last_mut();
+        if parent_node.is_some() {
+            parent_node.unwrap().children.push(node);
+        } else {
+            vdom.push(node);
+        }
+    }
+
+    /// Escapes the content of a raw text element, like `<style>`, so that evaluating it results in the
+    /// same content; i.e. its curly brackets don't start expressions and its `&`s don't start character
+    /// references.
+    fn escape_raw_text(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('{', "{{")
+            .replace('}', "}}")
+    }
+
+    /// Adds a text node, given the text exactly as it is written in the markup. Unless
+    /// `preserve_whitespace` is true or the text is inside an element like `<pre>`, its whitespace is
+    /// collapsed first, and it is dropped if nothing is left. Like in HTML, a newline right after the
+    /// opening tag of `<pre>` or `<textarea>` is ignored. Texts which only have whitespace are never
+    /// kept at the top level. The content of a raw text element, like `<script>`, is kept as it is.
+    fn push_text_node(
+        stack: &mut Vec<VirtualNode>,
+        vdom: &mut Vec<VirtualNode>,
+        text: &str,
+        span: Span,
+        preserve_whitespace: bool,
+    ) {
+        if stack.is_empty() && text.trim().is_empty() {
+            return;
+        }
+        let is_in_preserving_element = stack.iter().any(|node| {
+            matches!(&node.node_type, NodeType::Tag(name) if is_input_whitespace_preserving_element(name))
+        });
+        let is_raw_text = stack.last().is_some_and(|node| {
+            matches!(&node.node_type, NodeType::Tag(name) if is_input_raw_text_element(name))
+        });
+        if is_raw_text {
+            let new_node = VirtualNode {
+                node_type: NodeType::Text(escape_raw_text(text)),
+                attributes: HashMap::new(),
+                children: Vec::new(),
+                span,
+                attribute_spans: HashMap::new(),
+            };
+            push_completed_node(stack, vdom, new_node);
+            return;
         }
-        let msg = "Presenter of each component must be wrapped inside one and only one wrapper."
-            .to_owned();
-        return Err(CustomError::ParsingError(msg));
+        let text = if preserve_whitespace || is_in_preserving_element {
+            let starts_preserving_element = stack.last().is_some_and(|node| {
+                node.children.is_empty()
+                    && matches!(&node.node_type, NodeType::Tag(name) if is_input_whitespace_preserving_element(name))
+            });
+            let mut text = text;
+            if starts_preserving_element {
+                text = text
+                    .strip_prefix("\r\n")
+                    .or(text.strip_prefix('\n'))
+                    .unwrap_or(text);
+            }
+            text.to_owned()
+        } else {
+            collapse_whitespace(text)
+        };
+        if text.is_empty() {
+            return;
+        }
+        let new_node = VirtualNode {
+            node_type: NodeType::Text(text),
+            attributes: HashMap::new(),
+            children: Vec::new(),
+            span,
+            attribute_spans: HashMap::new(),
+        };
+        push_completed_node(stack, vdom, new_node);
     }
 
     /// Given an object of type `ParsedPresenter`, constructs a vdom using the `tokenizer` module.
     /// If an error is encountered, an `Err` variant is returned explaining why; `Ok` otherwise,
     /// which contains a `VirtualNode` object.
+    /// Void elements, like `<br>` or `<img src={...}>`, are closed as soon as their opening tag ends,
+    /// so they don't need a `/>`.
+    /// Components may be self-closing, like `<Card />`, or have children, like `<Card><p>hi</p></Card>`.
+    /// Fragments, `<>...</>`, become `Fragment` nodes, and so do presenters with several top-level nodes.
+    /// Comments are dropped, unless `set_keep_comments` was called with true.
+    /// Whitespace in texts is collapsed like JSX does, except inside `<pre>` and `<textarea>`; if
+    /// `preserve_whitespace` is true, it is kept as it is everywhere.
     pub async fn parse_vdom_from_string(
         parsed_file: &ParsedPresenter,
+        preserve_whitespace: bool,
+    ) -> Result<VirtualNode, CustomError> {
+        parse_vdom(parsed_file, preserve_whitespace, None).await
+    }
+
+    /// Like `parse_vdom_from_string`, but doesn't stop at the first error; instead, every problem of
+    /// the presenter is returned as a `Diagnostic`, along with a best-effort vdom:
+    /// 1- malformed tags and attributes are skipped; see `Tokenizer::recovering`.
+    /// 2- a closing tag which doesn't match the innermost open node closes the open node with its name,
+    ///    along with the nodes inside it; if there is none, it is ignored. Closing tags of void
+    ///    elements are ignored with a warning, since nothing is lost.
+    /// 3- components which can't be resolved are replaced with fragments, so their children are kept.
+    /// 4- nodes which are still open at the end of the presenter are closed there.
+    /// 5- a presenter without nodes results in an empty fragment.
+    pub async fn parse_vdom_from_string_recovering(
+        parsed_file: &ParsedPresenter,
+        preserve_whitespace: bool,
+    ) -> (VirtualNode, Vec<Diagnostic>) {
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        let vdom_result =
+            parse_vdom(parsed_file, preserve_whitespace, Some(&mut diagnostics)).await;
+        if vdom_result.is_err() {
+            // `parse_vdom` doesn't fail while recovering, but nothing is lost if it ever does
+            diagnostics.push(Diagnostic {
+                severity: Severity::Error,
+                error: vdom_result.unwrap_err(),
+            });
+            return (get_empty_fragment(), diagnostics);
+        }
+        (vdom_result.unwrap(), diagnostics)
+    }
+
+    /// Does the work of `parse_vdom_from_string`, and of `parse_vdom_from_string_recovering` if
+    /// `diagnostics` is `Some`; in which case, errors are recorded there and `Ok` is returned.
+    async fn parse_vdom(
+        parsed_file: &ParsedPresenter,
+        preserve_whitespace: bool,
+        mut diagnostics: Option<&mut Vec<Diagnostic>>,
     ) -> Result<VirtualNode, CustomError> {
         let ParsedPresenter { imports, markup } = parsed_file;
-        let mut get_next_token = tokenizer(markup.to_owned());
+        let mut tokens = if diagnostics.is_some() {
+            Tokenizer::recovering(markup)
+        } else {
+            Tokenizer::new(markup)
+        };
         let mut stack: Vec<VirtualNode> = Vec::new();
-        let mut stack_size: usize = 0;
         let mut vdom: Vec<VirtualNode> = Vec::new();
+        let mut tag_start = Span::default(); // span of the last `<` or `</`
+        let mut in_opening_tag = false; // whether the next `>` ends an opening tag
+        let mut previous_end: usize = 0; // where the previous token ended
         loop {
-            let next_token_result = get_next_token();
+            let next_token_option = tokens.next();
+            // the tokenizer skips the whitespace before a token, which is a part of the text between
+            // two tags; so texts are read back from the markup, along with the whitespace between
+            // two tags, which isn't a token at all.
+            let skipped_start = previous_end;
+            let skipped_end = match &next_token_option {
+                None => Some(markup.len()),
+                Some(Ok(Token {
+                    kind:
+                        TokenKind::OpenAngleBracket
+                        | TokenKind::ClosingAngleBracket
+                        | TokenKind::Comment(_),
+                    span,
+                })) => Some(span.start),
+                _ => None,
+            };
+            if skipped_end.is_some_and(|skipped_end| skipped_start < skipped_end) {
+                push_text_node(
+                    &mut stack,
+                    &mut vdom,
+                    &markup[skipped_start..skipped_end.unwrap()],
+                    Span::default(), // whitespace never causes an error to point at it
+                    preserve_whitespace,
+                );
+            }
+            if next_token_option.is_none() {
+                while !stack.is_empty() {
+                    let unclosed_node = stack.last().unwrap();
+                    let error = get_unclosed_node_error(unclosed_node, markup);
+                    let report_result = report(error, Severity::Error, &mut diagnostics);
+                    if report_result.is_err() {
+                        return Err(report_result.unwrap_err());
+                    }
+                    let mut completed_node = stack.pop().unwrap();
+                    completed_node.span.end = markup.len();
+                    push_completed_node(&mut stack, &mut vdom, completed_node);
+                }
+                let return_value = get_parser_return_value(vdom);
+                if return_value.is_err() && diagnostics.is_some() {
+                    let report_result =
+                        report(return_value.unwrap_err(), Severity::Error, &mut diagnostics);
+                    return report_result.map(|_| get_empty_fragment());
+                }
+                return return_value;
+            }
+            let next_token_result = next_token_option.unwrap();
             if next_token_result.is_err() {
-                return Err(next_token_result.unwrap_err());
+                let report_result = report(
+                    next_token_result.unwrap_err(),
+                    Severity::Error,
+                    &mut diagnostics,
+                );
+                if report_result.is_err() {
+                    return Err(report_result.unwrap_err());
+                }
+                // what was skipped is not a part of any text
+                previous_end = tokens.get_index();
+                continue;
             }
-            let CurrentState { state, token } = next_token_result.unwrap();
-            match state {
-                TokenizerState::Finalized => {
-                    return get_parser_return_value(stack);
+            let Token { kind, span } = next_token_result.unwrap();
+            previous_end = span.end;
+            match kind {
+                TokenKind::OpenAngleBracket | TokenKind::ClosingAngleBracket => {
+                    tag_start = span;
                 }
-                TokenizerState::TagNameClose => {
-                    let completed_node = stack.pop().unwrap();
-                    stack_size -= 1;
-                    if stack_size != 0 {
-                        let parent_node = stack.get_mut(stack_size - 1).unwrap();
-                        let children = &mut parent_node.children;
-                        children.push(completed_node);
-                    } else {
-                        vdom.push(completed_node);
-                        break;
+                TokenKind::TagNameClose(name) => {
+                    let closing_span = Span {
+                        end: span.end,
+                        ..tag_start
+                    };
+                    let completed_node_result = close_node(&mut stack, name, &closing_span, markup);
+                    if completed_node_result.is_err() {
+                        let severity = if is_input_void_element(name) {
+                            Severity::Warning
+                        } else {
+                            Severity::Error
+                        };
+                        let report_result = report(
+                            completed_node_result.unwrap_err(),
+                            severity,
+                            &mut diagnostics,
+                        );
+                        if report_result.is_err() {
+                            return Err(report_result.unwrap_err());
+                        }
+                        close_node_recovering(&mut stack, &mut vdom, name, &closing_span, markup);
+                        continue;
                     }
+                    let completed_node = completed_node_result.unwrap();
+                    push_completed_node(&mut stack, &mut vdom, completed_node);
+                }
+                TokenKind::Text(_) => {
+                    push_text_node(
+                        &mut stack,
+                        &mut vdom,
+                        &markup[skipped_start..span.end],
+                        span,
+                        preserve_whitespace,
+                    );
                 }
-                TokenizerState::Text => {
-                    let tag = NodeType::Text(token);
+                TokenKind::Comment(comment) => {
+                    if !KEEP_COMMENTS.load(Ordering::Relaxed) {
+                        continue;
+                    }
                     let new_node = VirtualNode {
-                        node_type: tag,
+                        node_type: NodeType::Comment(comment.to_owned()),
                         attributes: HashMap::new(),
                         children: Vec::new(),
+                        span,
+                        attribute_spans: HashMap::new(),
                     };
-                    if stack_size != 0 {
-                        let parent_node = stack.get_mut(stack_size - 1).unwrap();
-                        let children = &mut parent_node.children;
-                        children.push(new_node);
-                    } else {
-                        vdom.push(new_node);
-                        break;
-                    }
+                    push_completed_node(&mut stack, &mut vdom, new_node);
                 }
-                TokenizerState::TagNameOpen => {
-                    let tag = NodeType::Tag(token);
+                TokenKind::TagNameOpen(name) => {
+                    let tag = if name == FRAGMENT_TAG_NAME {
+                        NodeType::Fragment
+                    } else {
+                        NodeType::Tag(name.to_owned())
+                    };
                     let new_node = VirtualNode {
                         node_type: tag,
                         attributes: HashMap::new(),
                         children: Vec::new(),
+                        span: Span {
+                            end: span.end,
+                            ..tag_start
+                        },
+                        attribute_spans: HashMap::new(),
                     };
                     stack.push(new_node);
-                    stack_size += 1;
+                    in_opening_tag = true;
                 }
-                TokenizerState::Component => {
-                    let component_path = imports.get(&token);
-                    if let Option::None = component_path {
+                TokenKind::Component(name) => {
+                    let component_path = imports.get(name);
+                    let component = if let Option::None = component_path {
                         let msg = format!(
-                            "An import statement for `{token}` was supposed to exist, but it didn't."
+                            "An import statement for `{name}` was supposed to exist, but it didn't."
                         );
-                        return Err(CustomError::ReferenceError(msg));
-                    }
-                    let component_path = component_path.unwrap();
-                    let component = call_module_resolver(&component_path).await;
-                    if let Result::Err(err) = component {
-                        return Err(err);
-                    }
-                    let component = component.unwrap();
+                        Err(locate_error(
+                            CustomError::ReferenceError(msg),
+                            &span,
+                            markup,
+                        ))
+                    } else {
+                        call_module_resolver(component_path.unwrap()).await
+                    };
+                    let node_type = if let Result::Err(err) = component {
+                        let report_result = report(err, Severity::Error, &mut diagnostics);
+                        if report_result.is_err() {
+                            return Err(report_result.unwrap_err());
+                        }
+                        NodeType::Fragment
+                    } else {
+                        NodeType::Component(component.unwrap())
+                    };
                     stack.push(VirtualNode {
                         attributes: HashMap::new(),
                         children: Vec::new(),
-                        node_type: NodeType::Component(component),
+                        node_type,
+                        span: Span {
+                            end: span.end,
+                            ..tag_start
+                        },
+                        attribute_spans: HashMap::new(),
                     });
-                    stack_size += 1;
-                } // note that all Components are assumed to be self-closing at this point. The other variant is not handled
-                TokenizerState::Props => {
-                    let owner_node = stack.get_mut(stack_size - 1).unwrap();
-                    let attrs = &mut owner_node.attributes;
-                    let key_value_split = token.split_once(ATTRIBUTE_KEY_VALUE_SEPARATOR).unwrap();
-                    attrs.insert(key_value_split.0.to_owned(), key_value_split.1.to_owned());
                 }
-                TokenizerState::SelfClosingAngleBracket => {
-                    let completed_node = stack.pop().unwrap();
-                    stack_size -= 1;
-                    if stack_size != 0 {
-                        let parent_node = stack.get_mut(stack_size - 1).unwrap();
-                        let children = &mut parent_node.children;
-                        children.push(completed_node);
-                    } else {
-                        vdom.push(completed_node);
-                        break;
+                TokenKind::Prop { key, value } => {
+                    if stack.is_empty() {
+                        let msg = format!("Found the attribute `{key}` outside of any tag.");
+                        let error = locate_error(CustomError::ParsingError(msg), &span, markup);
+                        let report_result = report(error, Severity::Error, &mut diagnostics);
+                        if report_result.is_err() {
+                            return Err(report_result.unwrap_err());
+                        }
+                        continue;
+                    }
+                    let owner_node = stack.last_mut().unwrap();
+                    let value = value.unwrap_or(BOOLEAN_ATTRIBUTE_VALUE);
+                    owner_node
+                        .attributes
+                        .insert(key.to_owned(), value.to_owned());
+                    owner_node.attribute_spans.insert(key.to_owned(), span);
+                }
+                TokenKind::CloseAngleBracket => {
+                    let closes_void_element = in_opening_tag
+                        && stack.last().is_some_and(|node| {
+                            matches!(&node.node_type, NodeType::Tag(name) if is_input_void_element(name))
+                        });
+                    in_opening_tag = false;
+                    if closes_void_element {
+                        let mut completed_node = stack.pop().unwrap();
+                        completed_node.span.end = span.end;
+                        push_completed_node(&mut stack, &mut vdom, completed_node);
                     }
                 }
-                _ => {}
+                TokenKind::SelfClosingAngleBracket => {
+                    in_opening_tag = false;
+                    if stack.is_empty() {
+                        let msg =
+                            "Found `/>`, but there is no open tag for it to close.".to_owned();
+                        let error = locate_error(CustomError::ParsingError(msg), &span, markup);
+                        let report_result = report(error, Severity::Error, &mut diagnostics);
+                        if report_result.is_err() {
+                            return Err(report_result.unwrap_err());
+                        }
+                        continue;
+                    }
+                    let mut completed_node = stack.pop().unwrap();
+                    completed_node.span.end = span.end;
+                    push_completed_node(&mut stack, &mut vdom, completed_node);
+                }
             }
         }
-        return get_parser_return_value(vdom);
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn tag(name: &str, line: usize, column: usize) -> VirtualNode {
+            VirtualNode {
+                node_type: NodeType::Tag(name.to_owned()),
+                attributes: HashMap::new(),
+                children: Vec::new(),
+                span: Span {
+                    start: 0,
+                    end: 0,
+                    line,
+                    column,
+                },
+                attribute_spans: HashMap::new(),
+            }
+        }
+
+        const MARKUP: &str = "<div><span>hi</div></span>";
+
+        /// Returns the content of the text children of `node`.
+        fn texts_of(node: &VirtualNode) -> Vec<String> {
+            node.children
+                .iter()
+                .filter_map(|child| match &child.node_type {
+                    NodeType::Text(text) => Some(text.to_owned()),
+                    _ => None,
+                })
+                .collect()
+        }
+
+        #[test]
+        /// Whitespace must be collapsed by default, kept inside `<pre>` but for a newline right after
+        /// its opening tag, and kept everywhere if the component preserves whitespace.
+        fn test_push_text_node() {
+            let mut stack = vec![tag("p", 1, 1)];
+            let mut vdom: Vec<VirtualNode> = Vec::new();
+            push_text_node(
+                &mut stack,
+                &mut vdom,
+                "Hello\n  world",
+                Span::default(),
+                false,
+            );
+            push_text_node(&mut stack, &mut vdom, "\n  ", Span::default(), false);
+            push_text_node(&mut stack, &mut vdom, " ", Span::default(), false);
+            assert_eq!(texts_of(&stack[0]), vec!["Hello world", " "]);
+
+            let mut stack = vec![tag("pre", 1, 1), tag("code", 2, 1)];
+            push_text_node(&mut stack, &mut vdom, "\n  a\n", Span::default(), false);
+            assert_eq!(texts_of(&stack[1]), vec!["\n  a\n"]);
+            let mut stack = vec![tag("pre", 1, 1)];
+            push_text_node(&mut stack, &mut vdom, "\n  a\n", Span::default(), false);
+            assert_eq!(texts_of(&stack[0]), vec!["  a\n"]);
+
+            let mut stack = vec![tag("p", 1, 1)];
+            push_text_node(&mut stack, &mut vdom, "\n  a\n", Span::default(), true);
+            assert_eq!(texts_of(&stack[0]), vec!["\n  a\n"]);
+
+            push_text_node(&mut Vec::new(), &mut vdom, "\n  ", Span::default(), true);
+            assert!(vdom.is_empty());
+        }
+
+        #[test]
+        /// The content of a raw text element must be escaped, so evaluating it results in the content
+        /// as it was written.
+        fn test_push_text_node_raw_text() {
+            let mut stack = vec![tag("style", 1, 1)];
+            let mut vdom: Vec<VirtualNode> = Vec::new();
+            let css = "\n  .a{color:red}\n  .b::after{content:'&amp;'}\n";
+            push_text_node(&mut stack, &mut vdom, css, Span::default(), false);
+            assert_eq!(
+                texts_of(&stack[0]),
+                vec!["\n  .a{{color:red}}\n  .b::after{{content:'&amp;amp;'}}\n"]
+            );
+        }
+
+        #[test]
+        /// A single top-level node must be returned as is, while several of them must be wrapped in a
+        /// fragment; a presenter without any nodes is an error.
+        fn test_get_parser_return_value() {
+            let root_result = get_parser_return_value(vec![tag("main", 1, 1)]);
+            assert!(
+                matches!(root_result, Ok(node) if matches!(&node.node_type, NodeType::Tag(name) if name == "main"))
+            );
+            let root_result = get_parser_return_value(vec![tag("td", 1, 1), tag("td", 2, 1)]);
+            assert!(
+                matches!(root_result, Ok(node) if matches!(node.node_type, NodeType::Fragment) && node.children.len() == 2 && node.span.line == 1)
+            );
+            let root_result = get_parser_return_value(Vec::new());
+            assert!(matches!(root_result, Err(CustomError::ParsingError(_))));
+        }
+
+        const CLOSING_SPAN: Span = Span {
+            start: 13,
+            end: 18,
+            line: 1,
+            column: 14,
+        };
+
+        #[test]
+        /// `close_node` must pop the innermost open node when the closing tag matches it.
+        fn test_close_node() {
+            let mut stack = vec![tag("div", 1, 1), tag("span", 1, 6)];
+            let closed_result = close_node(&mut stack, "span", &CLOSING_SPAN, MARKUP);
+            assert!(
+                matches!(closed_result, Ok(node) if matches!(&node.node_type, NodeType::Tag(name) if name == "span") && node.span.end == 18)
+            );
+            assert_eq!(stack.len(), 1);
+        }
+
+        #[test]
+        /// A closing tag which doesn't match the innermost open tag must be reported, naming both.
+        fn test_close_node_mismatched() {
+            let mut stack = vec![tag("div", 1, 1), tag("span", 1, 6)];
+            let closed_result = close_node(&mut stack, "div", &CLOSING_SPAN, MARKUP);
+            assert!(
+                matches!(closed_result, Err(CustomError::ParsingError(msg)) if msg.contains("`</span>`") && msg.contains("`</div>`") && msg.contains("line 1, column 14"))
+            );
+        }
+
+        #[test]
+        /// A closing tag without any open tag must be reported.
+        fn test_close_node_stray() {
+            let mut stack: Vec<VirtualNode> = Vec::new();
+            let closed_result = close_node(&mut stack, "span", &CLOSING_SPAN, MARKUP);
+            assert!(
+                matches!(closed_result, Err(CustomError::ParsingError(msg)) if msg.contains("no open `<span>`"))
+            );
+        }
+
+        #[test]
+        /// Void elements never stay open, so their closing tags must be reported as such.
+        fn test_close_node_void_element() {
+            let mut stack = vec![tag("div", 1, 1)];
+            let closed_result = close_node(&mut stack, "br", &CLOSING_SPAN, MARKUP);
+            assert!(
+                matches!(closed_result, Err(CustomError::ParsingError(msg)) if msg.contains("void element"))
+            );
+            assert_eq!(stack.len(), 1);
+        }
+
+        #[test]
+        /// `</>` must close a fragment, and only a fragment.
+        fn test_close_node_fragment() {
+            let fragment = VirtualNode {
+                node_type: NodeType::Fragment,
+                ..tag("", 1, 6)
+            };
+            let mut stack = vec![tag("div", 1, 1), fragment];
+            let closed_result = close_node(&mut stack, FRAGMENT_TAG_NAME, &CLOSING_SPAN, MARKUP);
+            assert!(
+                matches!(closed_result, Ok(node) if matches!(node.node_type, NodeType::Fragment))
+            );
+            let closed_result = close_node(&mut stack, FRAGMENT_TAG_NAME, &CLOSING_SPAN, MARKUP);
+            assert!(
+                matches!(closed_result, Err(CustomError::ParsingError(msg)) if msg.contains("`</div>`") && msg.contains("`</>`"))
+            );
+        }
+
+        #[test]
+        /// Errors must be returned when not recovering, and recorded with their severity otherwise.
+        fn test_report() {
+            let error = CustomError::ParsingError("a".to_owned());
+            let report_result = report(error, Severity::Error, &mut None);
+            assert!(matches!(report_result, Err(CustomError::ParsingError(msg)) if msg == "a"));
+            let mut diagnostics: Vec<Diagnostic> = Vec::new();
+            let error = CustomError::ParsingError("b".to_owned());
+            let report_result = report(error, Severity::Warning, &mut Some(&mut diagnostics));
+            assert!(report_result.is_ok());
+            assert!(matches!(
+                &diagnostics[..],
+                [Diagnostic {
+                    severity: Severity::Warning,
+                    error: CustomError::ParsingError(_)
+                }]
+            ));
+        }
+
+        #[test]
+        /// A mismatched closing tag must close the open node with its name, along with the nodes inside
+        /// it; a closing tag without such a node must be ignored.
+        fn test_close_node_recovering() {
+            let mut stack = vec![tag("div", 1, 1), tag("span", 1, 6)];
+            let mut vdom: Vec<VirtualNode> = Vec::new();
+            close_node_recovering(&mut stack, &mut vdom, "p", &CLOSING_SPAN, MARKUP);
+            assert_eq!(stack.len(), 2);
+            close_node_recovering(&mut stack, &mut vdom, "div", &CLOSING_SPAN, MARKUP);
+            assert!(stack.is_empty());
+            assert_eq!(vdom.len(), 1);
+            assert_eq!(vdom[0].span.end, CLOSING_SPAN.end);
+            assert_eq!(vdom[0].children[0].span.end, CLOSING_SPAN.start);
+        }
+
+        /// Runs `future`, given that it never waits for anything, which is the case for parsing
+        /// presenters that don't use components.
+        fn run_to_completion<F: std::future::Future>(future: F) -> F::Output {
+            let mut context = std::task::Context::from_waker(std::task::Waker::noop());
+            let poll = std::pin::pin!(future).poll(&mut context);
+            match poll {
+                std::task::Poll::Ready(output) => output,
+                std::task::Poll::Pending => panic!("Expected the future to be ready."),
+            }
+        }
+
+        #[test]
+        /// Every error of a presenter must be reported at once, along with a vdom which has everything
+        /// that wasn't malformed.
+        fn test_parse_vdom_from_string_recovering() {
+            let parsed_presenter = ParsedPresenter {
+                imports: HashMap::new(),
+                markup: "<div id=x class=\"a\">\n  <p$>a</p$>\n  <span>b</p>\n  c<br></br>\n</div>\n<ul>"
+                    .to_owned(),
+            };
+            let (vdom, diagnostics) =
+                run_to_completion(parse_vdom_from_string_recovering(&parsed_presenter, false));
+            let messages: Vec<(Severity, String)> = diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.severity, diagnostic.to_string()))
+                .collect();
+            assert_eq!(messages.len(), 7);
+            assert!(messages[0].1.contains("line 1, column 6"));
+            assert!(messages[1].1.contains("line 2, column 4"));
+            assert!(messages[3].1.contains("found `</p>`"));
+            assert_eq!(messages[4].0, Severity::Warning);
+            assert!(messages[5].1.contains("found `</div>`"));
+            assert!(messages[6].1.contains("`<ul>` was never closed"));
+            assert!(matches!(vdom.node_type, NodeType::Fragment));
+            let div = &vdom.children[0];
+            assert_eq!(div.attributes.get("class").unwrap(), "\"a\"");
+            assert!(!div.attributes.contains_key("id"));
+            assert_eq!(texts_of(div), vec!["a"]);
+            let span = &div.children[1];
+            assert!(matches!(&span.node_type, NodeType::Tag(name) if name == "span"));
+            assert_eq!(texts_of(span), vec!["b", "c"]);
+            assert_eq!(span.children.len(), 3);
+            assert!(matches!(&vdom.children[1].node_type, NodeType::Tag(name) if name == "ul"));
+
+            let strict_result = run_to_completion(parse_vdom_from_string(&parsed_presenter, false));
+            assert!(
+                matches!(strict_result, Err(CustomError::ParsingError(msg)) if msg.contains("line 1, column 6"))
+            );
+
+            // malformed closing tags must be skipped entirely, whether or not something is open
+            let markups = [
+                ("<p>hi</p x>", 1),
+                ("<p>a</p $>", 1),
+                ("</p/>", 3), // a stray `</p>`, the `/` after it, and a presenter without nodes
+            ];
+            for (markup, diagnostic_count) in markups {
+                let parsed_presenter = ParsedPresenter {
+                    imports: HashMap::new(),
+                    markup: markup.to_owned(),
+                };
+                let (vdom, diagnostics) =
+                    run_to_completion(parse_vdom_from_string_recovering(&parsed_presenter, false));
+                assert_eq!(diagnostics.len(), diagnostic_count, "{markup}");
+                assert!(diagnostics
+                    .iter()
+                    .all(|diagnostic| diagnostic.severity == Severity::Error));
+                assert!(vdom.attributes.is_empty(), "{markup}");
+                if diagnostic_count == 1 {
+                    assert!(matches!(&vdom.node_type, NodeType::Tag(name) if name == "p"));
+                }
+                let strict_result =
+                    run_to_completion(parse_vdom_from_string(&parsed_presenter, false));
+                assert!(strict_result.is_err(), "{markup}");
+            }
+
+            // a `<` which doesn't start a tag must be kept as text
+            let parsed_presenter = ParsedPresenter {
+                imports: HashMap::new(),
+                markup: "<p>a < b</p>".to_owned(),
+            };
+            let (vdom, diagnostics) =
+                run_to_completion(parse_vdom_from_string_recovering(&parsed_presenter, false));
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(texts_of(&vdom), vec!["a ", "< b"]);
+        }
+
+        #[test]
+        /// Closing tags can't have props or `/`; they must be reported, naming the closing tag, rather
+        /// than being applied to the parent node or closing it.
+        fn test_parse_vdom_from_string_malformed_closing_tag() {
+            let markups = [
+                ("<div><p>hi</p x=\"1\"></div>", "line 1, column 15"),
+                ("<div><p>a</p/></div>", "line 1, column 13"),
+            ];
+            for (markup, location) in markups {
+                let parsed_presenter = ParsedPresenter {
+                    imports: HashMap::new(),
+                    markup: markup.to_owned(),
+                };
+                let vdom_result =
+                    run_to_completion(parse_vdom_from_string(&parsed_presenter, false));
+                assert!(
+                    matches!(&vdom_result, Err(CustomError::ParsingError(msg)) if msg.contains("closing tag `</p>`") && msg.contains(location)),
+                    "{markup}: {vdom_result:?}"
+                );
+            }
+        }
+
+        #[test]
+        /// A node which is still open at the end of the markup must be reported at its opening tag.
+        fn test_unclosed_node_error() {
+            let error = get_unclosed_node_error(&tag("span", 1, 6), MARKUP);
+            assert!(
+                matches!(error, CustomError::ParsingError(msg) if msg.contains("`<span>` was never closed") && msg.contains("line 1, column 6"))
+            );
+        }
     }
 }
@@ -0,0 +1,125 @@
+/// Decides what happens to the whitespace in the texts of a presenter.
+pub mod whitespace_mod {
+    use crate::const_util::const_util_mod::{
+        is_input_close_curly_bracket, is_input_open_curly_bracket,
+    };
+
+    /// Stands for an expression while the whitespace around it is collapsed. It is not a whitespace
+    /// char, so an expression on a line of its own keeps that line.
+    const EXPRESSION_PLACEHOLDER: char = '\u{FFFC}';
+
+    /// Replaces each expression in `text`, like `{state.name}`, with `EXPRESSION_PLACEHOLDER` and
+    /// returns the result along with the expressions, in order. Escaped curly brackets, `{{` and `}}`,
+    /// are kept as they are; so is an expression which is never closed.
+    fn extract_expressions(text: &str) -> (String, Vec<String>) {
+        let chars: Vec<char> = text.chars().collect();
+        let mut without_expressions = String::new();
+        let mut expressions: Vec<String> = Vec::new();
+        let mut expression = String::new();
+        let mut depth = 0;
+        let mut index = 0;
+        while index < chars.len() {
+            let current = chars[index];
+            let next = chars.get(index + 1).copied();
+            if depth == 0 {
+                let is_escaped = (is_input_open_curly_bracket(current)
+                    || is_input_close_curly_bracket(current))
+                    && next == Some(current);
+                if is_escaped {
+                    without_expressions.push(current);
+                    without_expressions.push(current);
+                    index += 2;
+                    continue;
+                }
+                if !is_input_open_curly_bracket(current) {
+                    without_expressions.push(current);
+                    index += 1;
+                    continue;
+                }
+            }
+            if is_input_open_curly_bracket(current) {
+                depth += 1;
+            } else if is_input_close_curly_bracket(current) {
+                depth -= 1;
+            }
+            expression.push(current);
+            if depth == 0 {
+                without_expressions.push(EXPRESSION_PLACEHOLDER);
+                expressions.push(expression);
+                expression = String::new();
+            }
+            index += 1;
+        }
+        without_expressions.push_str(&expression);
+        (without_expressions, expressions)
+    }
+
+    /// Collapses the whitespace of a text the way JSX does. The text is split into lines; whitespace
+    /// at the start of every line but the first, and at the end of every line but the last, is
+    /// removed. Lines which become empty are dropped and the rest are joined with a single space. So
+    /// a text which only has whitespace disappears if it spans several lines, like the indentation
+    /// between two tags, and is kept otherwise, like the space in `<b>a</b> <i>b</i>`.
+    /// Expressions are left untouched.
+    pub fn collapse_whitespace(text: &str) -> String {
+        let (without_expressions, expressions) = extract_expressions(text);
+        let lines: Vec<&str> = without_expressions.split('\n').collect();
+        let last_line = lines.len() - 1;
+        let mut collapsed_lines: Vec<&str> = Vec::new();
+        for (index, line) in lines.into_iter().enumerate() {
+            let mut line = line;
+            if index != 0 {
+                line = line.trim_start();
+            }
+            if index != last_line {
+                line = line.trim_end();
+            }
+            if !line.is_empty() {
+                collapsed_lines.push(line);
+            }
+        }
+        let mut expressions = expressions.into_iter();
+        let mut collapsed = String::new();
+        for chr in collapsed_lines.join(" ").chars() {
+            if chr == EXPRESSION_PLACEHOLDER {
+                collapsed.push_str(&expressions.next().unwrap_or_default());
+            } else {
+                collapsed.push(chr);
+            }
+        }
+        collapsed
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        /// Lines must be trimmed and joined with a single space, and texts which only have whitespace
+        /// must only survive if they don't span several lines.
+        fn test_collapse_whitespace() {
+            assert_eq!(collapse_whitespace("Hello\n    world"), "Hello world");
+            assert_eq!(collapse_whitespace("\n  Hello\n\n  world\n"), "Hello world");
+            assert_eq!(collapse_whitespace("Hello  "), "Hello  ");
+            assert_eq!(collapse_whitespace(" and "), " and ");
+            assert_eq!(collapse_whitespace(" "), " ");
+            assert_eq!(collapse_whitespace("\n    "), "");
+            assert_eq!(collapse_whitespace("a\r\n  b"), "a b");
+        }
+
+        #[test]
+        /// Expressions must be left untouched, even if they span several lines, while escaped curly
+        /// brackets are treated like any other char.
+        fn test_collapse_whitespace_expressions() {
+            assert_eq!(
+                collapse_whitespace("\n  {state.a}\n  {`x\n  y`}\n"),
+                "{state.a} {`x\n  y`}"
+            );
+            assert_eq!(
+                collapse_whitespace("{{\n  {props.x}\n}}"),
+                "{{ {props.x} }}"
+            );
+            assert_eq!(collapse_whitespace("{ {a: 1}.a }"), "{ {a: 1}.a }");
+            assert_eq!(collapse_whitespace("a {\n  b"), "a { b");
+        }
+    }
+}
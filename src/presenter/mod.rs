@@ -37,21 +37,13 @@ pub mod presenter_mod {
         return Ok(());
     }
 
-    /// Removes any line which contains nothing but whitespace characters.
-    fn trim_markup(markup: Vec<String>) -> String {
-        let mut res = String::from("");
-        for line in markup {
-            if line.trim() != "" {
-                res.push_str(&line);
-            }
-        }
-        res
-    }
-
     /// Given a string, parses its content into a `ParsedPresenter`, if done successfully; else,
     /// returns a `Err` variant which contains the reason why.
+    /// Import statements are replaced with whitespace in `markup` rather than removed, so that
+    /// `markup` has the same length and lines as `presenter`, and spans of its tokens point to the
+    /// right place in the presenter.
     pub fn parse_presenter(presenter: &String) -> Result<ParsedPresenter, Error> {
-        let split_presenter: Vec<&str> = presenter.trim().split('\n').collect();
+        let split_presenter: Vec<&str> = presenter.split('\n').collect();
         let mut in_markup = false;
         let mut imports: HashMap<String, String> = HashMap::new();
         let mut markup: Vec<String> = Vec::new();
@@ -61,14 +53,15 @@ pub mod presenter_mod {
                 if let Result::Err(err) = read_import_result {
                     return Err(err);
                 }
+                markup.push(" ".repeat(line.len()));
             } else {
-                if !in_markup {
+                if !in_markup && line.trim() != "" {
                     in_markup = true;
                 }
                 markup.push(line.to_owned());
             }
         }
-        let markup = trim_markup(markup);
+        let markup = markup.join("\n");
 
         Ok(ParsedPresenter { imports, markup })
     }
@@ -161,5 +154,18 @@ pub mod presenter_mod {
                 assert!(false);
             }
         }
+
+        #[test]
+        /// Lines of the markup must match the lines of the presenter, so spans of tokens point to the
+        /// right place in the presenter.
+        fn test_parse_presenter_keeps_lines() {
+            let presenter = String::from(
+                "import Hello from \"/test/Hello/Hello.js\";\n\n<div>\n  <Hello />\n</div>",
+            );
+            let ParsedPresenter { markup, .. } = parse_presenter(&presenter).unwrap();
+            assert_eq!(markup.len(), presenter.len());
+            assert_eq!(markup.lines().nth(3), Some("  <Hello />"));
+            assert!(markup.lines().next().unwrap().trim().is_empty());
+        }
     }
 }
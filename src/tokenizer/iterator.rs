@@ -0,0 +1,769 @@
+/// An iterator over the tokens of a markup, whose tokens borrow their text from it.
+pub mod iterator_mod {
+    use crate::{
+        const_util::const_util_mod::{
+            is_input_close_angle_bracket, is_input_close_curly_bracket,
+            is_input_escapable_raw_text_element, is_input_open_angle_bracket,
+            is_input_open_curly_bracket, is_input_quotation_mark, is_input_raw_text_element,
+            ATTRIBUTE_KEY_VALUE_SEPARATOR, BOOLEAN_ATTRIBUTE_VALUE, CLOSE_ANGLE_BRACKET,
+            CLOSING_TAG, COMMENT_END, COMMENT_START, OPEN_ANGLE_BRACKET, SELF_CLOSING_TAG,
+        },
+        error::error_mod::{locate_error, Error},
+        tokenizer::tokenizer_mod::{is_valid_tag_name, CurrentState, Span, TokenizerState},
+    };
+
+    /// A token of a markup. Names, texts and values are slices of the markup, so reading a token
+    /// doesn't allocate.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum TokenKind<'a> {
+        OpenAngleBracket,        // <
+        CloseAngleBracket,       // >
+        SelfClosingAngleBracket, // />
+        ClosingAngleBracket,     // </
+        TagNameOpen(&'a str),
+        TagNameClose(&'a str),
+        Component(&'a str),
+        Prop {
+            key: &'a str,
+            value: Option<&'a str>, // `None` for a boolean attribute, like `disabled`
+        },
+        Text(&'a str),
+        Comment(&'a str),
+    }
+
+    /// A token along with the span of the markup it was read from.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Token<'a> {
+        pub kind: TokenKind<'a>,
+        pub span: Span,
+    }
+
+    impl<'a> Token<'a> {
+        /// Returns the `TokenizerState` which `tokenizer` reports for this kind of token.
+        pub fn get_state(&self) -> TokenizerState {
+            match self.kind {
+                TokenKind::OpenAngleBracket => TokenizerState::OpenAngleBracket,
+                TokenKind::CloseAngleBracket => TokenizerState::CloseAngleBracket,
+                TokenKind::SelfClosingAngleBracket => TokenizerState::SelfClosingAngleBracket,
+                TokenKind::ClosingAngleBracket => TokenizerState::ClosingAngleBracket,
+                TokenKind::TagNameOpen(_) => TokenizerState::TagNameOpen,
+                TokenKind::TagNameClose(_) => TokenizerState::TagNameClose,
+                TokenKind::Component(_) => TokenizerState::Component,
+                TokenKind::Prop { .. } => TokenizerState::Props,
+                TokenKind::Text(_) => TokenizerState::Text,
+                TokenKind::Comment(_) => TokenizerState::Comment,
+            }
+        }
+    }
+
+    impl<'a> From<Token<'a>> for CurrentState {
+        /// Converts the token to what `tokenizer` returns for it; e.g. a prop becomes `key=value`.
+        fn from(token: Token<'a>) -> Self {
+            let text = match token.kind {
+                TokenKind::OpenAngleBracket => OPEN_ANGLE_BRACKET.to_string(),
+                TokenKind::CloseAngleBracket => CLOSE_ANGLE_BRACKET.to_string(),
+                TokenKind::SelfClosingAngleBracket => SELF_CLOSING_TAG.to_owned(),
+                TokenKind::ClosingAngleBracket => CLOSING_TAG.to_owned(),
+                TokenKind::TagNameOpen(text)
+                | TokenKind::TagNameClose(text)
+                | TokenKind::Component(text)
+                | TokenKind::Text(text)
+                | TokenKind::Comment(text) => text.to_owned(),
+                TokenKind::Prop { key, value } => {
+                    key.to_owned()
+                        + ATTRIBUTE_KEY_VALUE_SEPARATOR
+                        + value.unwrap_or(BOOLEAN_ATTRIBUTE_VALUE)
+                }
+            };
+            CurrentState {
+                state: token.get_state(),
+                token: text,
+                span: token.span,
+            }
+        }
+    }
+
+    /// Where the last span started; lines and columns are counted from here, so the markup is only
+    /// walked once.
+    #[derive(Debug, Clone, Copy)]
+    struct Cursor {
+        offset: usize,
+        line: usize,
+        column: usize,
+    }
+
+    /// What went wrong while reading a token, and the byte offset right after where it went wrong.
+    type TokenError = (Error, usize);
+
+    /// Tokenizes a markup the same way `tokenizer` does, but works on the markup in place: offsets are
+    /// byte offsets into it and tokens borrow from it. Iteration stops at the end of the markup, or
+    /// after the first error, which is located in the markup like the errors of `tokenizer`; unless it
+    /// was created with `Tokenizer::recovering`, in which case it skips past what was malformed and
+    /// goes on.
+    pub struct Tokenizer<'a> {
+        markup: &'a str,
+        index: usize, // where the next token may start
+        state: TokenizerState,
+        raw_text_element: Option<&'a str>, // the raw text element whose opening tag is being read
+        closing_tag_name: &'a str,         // name of the closing tag which is being read
+        cursor: Cursor,
+        recover: bool,
+        tag_start: usize,   // where the last `<` or `</` started
+        literal_text: bool, // whether the `<` at `index` is a part of a text, as no tag followed it
+    }
+
+    impl<'a> Tokenizer<'a> {
+        pub fn new(markup: &'a str) -> Self {
+            Tokenizer {
+                markup,
+                index: 0,
+                state: TokenizerState::Uninitialized,
+                raw_text_element: None,
+                closing_tag_name: "",
+                cursor: Cursor {
+                    offset: 0,
+                    line: 1,
+                    column: 1,
+                },
+                recover: false,
+                tag_start: 0,
+                literal_text: false,
+            }
+        }
+
+        /// Like `Tokenizer::new`, but an error doesn't end the iteration:
+        /// 1- a malformed attribute, or anything else inside a tag, is skipped up to the next
+        ///    whitespace or `>`, and the rest of the tag is read as usual.
+        /// 2- a malformed tag name skips the whole tag, up to its `>`; if another tag starts before
+        ///    that, like in `a < b</p>`, there was no tag at all, so the `<` and what follows it are
+        ///    read as text.
+        /// 3- anything but `>` after the name of a closing tag skips the rest of the closing tag, up to
+        ///    its `>` or the next tag, so closing tags never have props.
+        /// 4- errors which reach the end of the markup, like an unclosed comment, end the iteration.
+        pub fn recovering(markup: &'a str) -> Self {
+            Tokenizer {
+                recover: true,
+                ..Tokenizer::new(markup)
+            }
+        }
+
+        /// Returns the offset where the next token may start; after an error, that's where the
+        /// tokenizer goes on from.
+        pub fn get_index(&self) -> usize {
+            self.index
+        }
+
+        fn char_at(&self, index: usize) -> Option<char> {
+            self.markup[index..].chars().next()
+        }
+
+        /// Returns the offset of the first char at or after `index` which is not a whitespace.
+        fn skip_whitespace(&self, index: usize) -> usize {
+            self.markup[index..]
+                .find(|c: char| !c.is_whitespace())
+                .map(|offset| index + offset)
+                .unwrap_or(self.markup.len())
+        }
+
+        /// Returns the span from `start` to `end`, which must not start before the previous one.
+        fn get_span(&mut self, start: usize, end: usize) -> Span {
+            if start < self.cursor.offset {
+                self.cursor = Cursor {
+                    offset: 0,
+                    line: 1,
+                    column: 1,
+                };
+            }
+            for current in self.markup[self.cursor.offset..start].chars() {
+                if current == '\n' {
+                    self.cursor.line += 1;
+                    self.cursor.column = 1;
+                } else {
+                    self.cursor.column += 1;
+                }
+            }
+            self.cursor.offset = start;
+            Span {
+                start,
+                end: end.max(start),
+                line: self.cursor.line,
+                column: self.cursor.column,
+            }
+        }
+
+        /// Returns the offset of the closing tag of the raw text element `tag_name`, like `</style>`,
+        /// which comes first after `index`.
+        fn find_closing_tag(&self, index: usize, tag_name: &str) -> Option<usize> {
+            let mut current = index;
+            loop {
+                let closing_tag_start = current + self.markup[current..].find(CLOSING_TAG)?;
+                let name_start = closing_tag_start + CLOSING_TAG.len();
+                let name = self.markup.get(name_start..name_start + tag_name.len());
+                let after_name = self.char_at(name_start + tag_name.len()).unwrap_or(' ');
+                let is_closing_tag = name.is_some_and(|name| name.eq_ignore_ascii_case(tag_name))
+                    && name_start + tag_name.len() < self.markup.len()
+                    && (is_input_close_angle_bracket(after_name)
+                        || after_name == '/'
+                        || after_name.is_whitespace());
+                if is_closing_tag {
+                    return Some(closing_tag_start);
+                }
+                current = name_start;
+            }
+        }
+
+        /// Reads the content of the raw text element `tag_name` as a single text; see `read_raw_text`.
+        fn read_raw_text(
+            &self,
+            tag_name: &str,
+        ) -> Result<Option<(TokenKind<'a>, usize)>, TokenError> {
+            let closing_tag_start = self.find_closing_tag(self.index, tag_name);
+            if closing_tag_start.is_none() {
+                let msg = format!(
+                    "`<{tag_name}>` was never closed; expected a `</{tag_name}>` before the end of the presenter."
+                );
+                return Err((Error::ParsingError(msg), self.markup.len()));
+            }
+            let closing_tag_start = closing_tag_start.unwrap();
+            if closing_tag_start == self.index {
+                return Ok(None);
+            }
+            let text = &self.markup[self.index..closing_tag_start];
+            Ok(Some((TokenKind::Text(text), closing_tag_start)))
+        }
+
+        /// Reads a text, a comment, `<` or `</`, given that the previous token ended a tag, a text or
+        /// a comment. `None` is returned once the markup is exhausted. If `literal_text` is set, the
+        /// `<` at `start` is read as the beginning of a text.
+        fn read_content(&self, start: usize) -> Result<Option<(TokenKind<'a>, usize)>, TokenError> {
+            let max = self.markup.len();
+            if start == max {
+                return Ok(None);
+            }
+            let text_start = if self.literal_text {
+                start + OPEN_ANGLE_BRACKET.len_utf8()
+            } else {
+                start
+            };
+            let text_end = self.markup[text_start..]
+                .find(is_input_open_angle_bracket)
+                .map(|offset| text_start + offset)
+                .unwrap_or(max);
+            if text_end != start {
+                let text = &self.markup[start..text_end];
+                return Ok(Some((TokenKind::Text(text), text_end)));
+            }
+            if self.markup[start..].starts_with(COMMENT_START) {
+                let content_start = start + COMMENT_START.len();
+                let content_length = self.markup[content_start..].find(COMMENT_END);
+                if content_length.is_none() {
+                    let msg = format!(
+                        "A comment was never closed; expected a `{COMMENT_END}` before the end of the presenter."
+                    );
+                    return Err((Error::ParsingError(msg), max));
+                }
+                let content_end = content_start + content_length.unwrap();
+                let comment = &self.markup[content_start..content_end];
+                return Ok(Some((
+                    TokenKind::Comment(comment),
+                    content_end + COMMENT_END.len(),
+                )));
+            }
+            let after_bracket = self.skip_whitespace(start + OPEN_ANGLE_BRACKET.len_utf8());
+            if self.char_at(after_bracket) == Some('/') {
+                return Ok(Some((TokenKind::ClosingAngleBracket, after_bracket + 1)));
+            }
+            Ok(Some((
+                TokenKind::OpenAngleBracket,
+                start + OPEN_ANGLE_BRACKET.len_utf8(),
+            )))
+        }
+
+        /// Reads the name of a tag, given that the previous token was `<` or `</`. Names which start
+        /// with an uppercase letter are components, unless they are closing one; see
+        /// `get_state_after_tag_name`.
+        fn read_tag_name(&self, start: usize) -> Result<(TokenKind<'a>, usize), TokenError> {
+            let max = self.markup.len();
+            let is_closing = self.state == TokenizerState::ClosingAngleBracket;
+            if start == max {
+                let msg = "No tag name was found after open angle bracket.".to_owned();
+                return Err((Error::ParsingError(msg), max));
+            }
+            let name_end = self.markup[start..]
+                .find(|c: char| c.is_whitespace() || is_input_close_angle_bracket(c) || c == '/')
+                .map(|offset| start + offset)
+                .unwrap_or(max);
+            let name = &self.markup[start..name_end];
+            if name_end == start && !is_input_close_angle_bracket(self.char_at(start).unwrap()) {
+                let msg = "Expected a tag name after the angle bracket, but found none.".to_owned();
+                return Err((Error::ParsingError(msg), start + 1));
+            }
+            let first_letter = name.chars().next();
+            let is_uppercase = first_letter.is_some_and(|c| c.is_uppercase());
+            let is_component_name = is_uppercase && name.chars().all(|c| c.is_alphanumeric());
+            if !name.is_empty() && !is_component_name && !is_valid_tag_name(name) {
+                let msg = format!("Provided tag name `{name}` contains invalid characters.");
+                return Err((Error::ParsingError(msg), name_end));
+            }
+            let kind = if is_uppercase && !is_closing {
+                TokenKind::Component(name)
+            } else if is_closing {
+                TokenKind::TagNameClose(name)
+            } else {
+                TokenKind::TagNameOpen(name)
+            };
+            Ok((kind, name_end))
+        }
+
+        /// Reads the value of a prop, like `{state.x}` or `"btn"`, given that `start` is where its
+        /// first char is. Curly brackets inside JS strings of the value don't close it.
+        fn read_value_of_prop(&self, start: usize) -> Result<(&'a str, usize), TokenError> {
+            let max = self.markup.len();
+            let end_error = || {
+                let msg = "Expected a key-value pair, but reached the end of markup.".to_owned();
+                (Error::ParsingError(msg), max)
+            };
+            let value_wrapper = self.char_at(start);
+            if value_wrapper.is_none() {
+                return Err(end_error());
+            }
+            let value_wrapper = value_wrapper.unwrap();
+            if is_input_quotation_mark(value_wrapper) {
+                let value_length = self.markup[start + 1..].find(value_wrapper);
+                if value_length.is_none() {
+                    let msg = format!(
+                        "Expected a closing {value_wrapper} for the value of an attribute, but reached the end of markup."
+                    );
+                    return Err((Error::ParsingError(msg), max));
+                }
+                let value_end = start + 1 + value_length.unwrap() + 1;
+                return Ok((&self.markup[start..value_end], value_end));
+            }
+            if !is_input_open_curly_bracket(value_wrapper) {
+                let msg = format!("Value of props and attributes must be wrapped around curly brackets or quotation marks. Provided char was {value_wrapper}");
+                return Err((Error::ParsingError(msg), start + value_wrapper.len_utf8()));
+            }
+            let mut depth = 0;
+            let mut open_quote: Option<char> = None; // the quotation mark of the JS string we are in, if any
+            let mut chars = self.markup[start..].char_indices();
+            while let Some((offset, current)) = chars.next() {
+                if open_quote.is_some() {
+                    if current == '\\' {
+                        chars.next();
+                    } else if Some(current) == open_quote {
+                        open_quote = None;
+                    }
+                } else if is_input_quotation_mark(current) || current == '`' {
+                    open_quote = Some(current);
+                } else if is_input_open_curly_bracket(current) {
+                    depth += 1;
+                } else if is_input_close_curly_bracket(current) {
+                    depth -= 1;
+                }
+                if depth == 0 {
+                    let value_end = start + offset + current.len_utf8();
+                    return Ok((&self.markup[start..value_end], value_end));
+                }
+            }
+            Err(end_error())
+        }
+
+        /// Reads a prop, like `key={value}`, `key="value"` or a boolean attribute like `disabled`.
+        /// Whitespace around the separator is skipped.
+        fn read_prop(&self, start: usize) -> Result<(TokenKind<'a>, usize), TokenError> {
+            let max = self.markup.len();
+            let end_error = || {
+                let msg = "Expected a key-value pair, but reached the end of markup.".to_owned();
+                (Error::ParsingError(msg), max)
+            };
+            let key_end = self.markup[start..]
+                .find(|c: char| {
+                    c.is_whitespace()
+                        || is_input_close_angle_bracket(c)
+                        || c == '/'
+                        || ATTRIBUTE_KEY_VALUE_SEPARATOR.starts_with(c)
+                })
+                .map(|offset| start + offset);
+            if key_end.is_none() {
+                return Err(end_error());
+            }
+            let key_end = key_end.unwrap();
+            let key = &self.markup[start..key_end];
+            let separator = self.skip_whitespace(key_end);
+            if separator == max {
+                return Err(end_error());
+            }
+            if key.is_empty() {
+                let msg = "Expected the name of an attribute, but found none.".to_owned();
+                return Err((Error::ParsingError(msg), key_end + 1));
+            }
+            if !self.markup[separator..].starts_with(ATTRIBUTE_KEY_VALUE_SEPARATOR) {
+                return Ok((TokenKind::Prop { key, value: None }, key_end));
+            }
+            let value_start = self.skip_whitespace(separator + ATTRIBUTE_KEY_VALUE_SEPARATOR.len());
+            let value_result = self.read_value_of_prop(value_start);
+            if value_result.is_err() {
+                return Err(value_result.unwrap_err());
+            }
+            let (value, value_end) = value_result.unwrap();
+            Ok((
+                TokenKind::Prop {
+                    key,
+                    value: Some(value),
+                },
+                value_end,
+            ))
+        }
+
+        /// Reads the `>` which ends a closing tag, given that the previous token was its name. Closing
+        /// tags can't have props or `/`, so anything else is an error naming the closing tag.
+        fn read_after_closing_tag_name(
+            &self,
+            start: usize,
+        ) -> Result<(TokenKind<'a>, usize), TokenError> {
+            let current = self.char_at(start);
+            if current.is_none() {
+                let msg =
+                    "Expected a closing angle bracket, but reached the end of markup.".to_owned();
+                return Err((Error::ParsingError(msg), self.markup.len()));
+            }
+            let current = current.unwrap();
+            if !is_input_close_angle_bracket(current) {
+                let tag_name = self.closing_tag_name;
+                let msg = format!("Expected `>` to end the closing tag `</{tag_name}>`, but found `{current}`; closing tags can't have attributes or `/`.");
+                return Err((Error::ParsingError(msg), start + current.len_utf8()));
+            }
+            Ok((TokenKind::CloseAngleBracket, start + 1))
+        }
+
+        /// Reads `>`, `/>` or a prop, given that the previous token was the name of a tag or a prop.
+        fn read_inside_tag(&self, start: usize) -> Result<(TokenKind<'a>, usize), TokenError> {
+            let max = self.markup.len();
+            let current = self.char_at(start);
+            if current.is_none() {
+                let msg =
+                    "Expected a closing angle bracket, but reached the end of markup.".to_owned();
+                return Err((Error::ParsingError(msg), max));
+            }
+            let current = current.unwrap();
+            if is_input_close_angle_bracket(current) {
+                return Ok((TokenKind::CloseAngleBracket, start + 1));
+            }
+            if current == '/' {
+                let after_slash = self.skip_whitespace(start + 1);
+                if self.char_at(after_slash) == Some(CLOSE_ANGLE_BRACKET) {
+                    return Ok((TokenKind::SelfClosingAngleBracket, after_slash + 1));
+                }
+                let msg = "Expected a closing angle bracket, but did not find it.".to_owned();
+                let error_end = self
+                    .char_at(after_slash)
+                    .map_or(max, |current| after_slash + current.len_utf8());
+                return Err((Error::ParsingError(msg), error_end));
+            }
+            self.read_prop(start)
+        }
+
+        /// Returns where to go on from, and the state to go on with, after an error which ended at
+        /// `error_end`, given that `start` is where the token was supposed to start; `None` if there
+        /// is nothing left to read. See `Tokenizer::recovering`.
+        fn get_recovery_point(
+            &self,
+            start: usize,
+            error_end: usize,
+        ) -> Option<(usize, TokenizerState)> {
+            let max = self.markup.len();
+            if error_end >= max {
+                return None;
+            }
+            match self.state {
+                TokenizerState::OpenAngleBracket | TokenizerState::ClosingAngleBracket => {
+                    let rest = &self.markup[start..];
+                    let tag_end = rest.find(is_input_close_angle_bracket);
+                    let next_tag_start = rest.find(is_input_open_angle_bracket);
+                    let resume = match (tag_end, next_tag_start) {
+                        (Some(tag_end), Some(next_tag_start)) if next_tag_start < tag_end => {
+                            self.tag_start
+                        }
+                        (Some(tag_end), _) => start + tag_end + 1,
+                        (None, _) => self.tag_start,
+                    };
+                    Some((resume, TokenizerState::Uninitialized))
+                }
+                TokenizerState::TagNameClose => {
+                    let rest = &self.markup[start..];
+                    let tag_end = rest.find(is_input_close_angle_bracket);
+                    let next_tag_start = rest.find(is_input_open_angle_bracket);
+                    let resume = match (tag_end, next_tag_start) {
+                        (Some(tag_end), Some(next_tag_start)) if next_tag_start < tag_end => {
+                            start + next_tag_start
+                        }
+                        (Some(tag_end), _) => start + tag_end + 1,
+                        (None, Some(next_tag_start)) => start + next_tag_start,
+                        (None, None) => return None,
+                    };
+                    Some((resume, TokenizerState::Uninitialized))
+                }
+                TokenizerState::TagNameOpen | TokenizerState::Component | TokenizerState::Props => {
+                    let resume = self.markup[error_end..]
+                        .find(|c: char| c.is_whitespace() || is_input_close_angle_bracket(c))
+                        .map(|offset| error_end + offset);
+                    resume.map(|resume| (resume, TokenizerState::Props))
+                }
+                _ => None,
+            }
+        }
+    }
+
+    impl<'a> Iterator for Tokenizer<'a> {
+        type Item = Result<Token<'a>, Error>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            let start = self.skip_whitespace(self.index.min(self.markup.len()));
+            let mut read_result: Result<Option<(TokenKind<'a>, usize)>, TokenError> = Ok(None);
+            if self.state == TokenizerState::CloseAngleBracket && self.raw_text_element.is_some() {
+                let tag_name = self.raw_text_element.take().unwrap();
+                read_result = self.read_raw_text(tag_name);
+            }
+            if matches!(read_result, Ok(None)) {
+                read_result = match self.state {
+                    TokenizerState::Uninitialized
+                    | TokenizerState::SelfClosingAngleBracket
+                    | TokenizerState::CloseAngleBracket
+                    | TokenizerState::Text
+                    | TokenizerState::Comment => self.read_content(start),
+                    TokenizerState::OpenAngleBracket | TokenizerState::ClosingAngleBracket => {
+                        self.read_tag_name(start).map(Some)
+                    }
+                    TokenizerState::TagNameClose => {
+                        self.read_after_closing_tag_name(start).map(Some)
+                    }
+                    TokenizerState::TagNameOpen
+                    | TokenizerState::Component
+                    | TokenizerState::Props => self.read_inside_tag(start).map(Some),
+                    TokenizerState::Finalized => return None,
+                };
+            }
+            if read_result.is_err() {
+                let (error, error_end) = read_result.unwrap_err();
+                let span = self.get_span(start, error_end);
+                let recovery_point = if self.recover {
+                    self.get_recovery_point(start, error_end)
+                } else {
+                    None
+                };
+                if recovery_point.is_some() {
+                    let (resume, state) = recovery_point.unwrap();
+                    // what follows a `<` which doesn't start a tag is a text, along with the `<`
+                    self.literal_text = resume == self.tag_start;
+                    self.index = resume;
+                    self.state = state;
+                } else {
+                    self.state = TokenizerState::Finalized;
+                }
+                return Some(Err(locate_error(error, &span, self.markup)));
+            }
+            let read_option = read_result.unwrap();
+            if read_option.is_none() {
+                self.state = TokenizerState::Finalized;
+                return None;
+            }
+            let (kind, end) = read_option.unwrap();
+            let token = Token {
+                kind,
+                span: self.get_span(start, end),
+            };
+            self.state = token.get_state();
+            self.literal_text = false;
+            match kind {
+                TokenKind::TagNameOpen(name)
+                    if is_input_raw_text_element(name)
+                        || is_input_escapable_raw_text_element(name) =>
+                {
+                    self.raw_text_element = Some(name);
+                }
+                TokenKind::TagNameOpen(_)
+                | TokenKind::Component(_)
+                | TokenKind::SelfClosingAngleBracket => self.raw_text_element = None,
+                TokenKind::TagNameClose(name) => self.closing_tag_name = name,
+                TokenKind::OpenAngleBracket | TokenKind::ClosingAngleBracket => {
+                    self.tag_start = token.span.start
+                }
+                _ => {}
+            }
+            self.index = end;
+            Some(Ok(token))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+
+        use super::*;
+        use crate::tokenizer::tokenizer_mod::tokenizer;
+
+        /// Collects what `tokenizer` returns for a markup, up to its end or its first error.
+        fn collect_reference_tokens(markup: &str) -> (Vec<CurrentState>, Option<String>) {
+            let mut generator = tokenizer(markup.to_owned());
+            let mut tokens: Vec<CurrentState> = Vec::new();
+            loop {
+                let next_result = generator();
+                if next_result.is_err() {
+                    return (tokens, Some(format!("{:?}", next_result.unwrap_err())));
+                }
+                let current_state = next_result.unwrap();
+                if matches!(current_state.state, TokenizerState::Finalized) {
+                    return (tokens, None);
+                }
+                tokens.push(current_state);
+            }
+        }
+
+        /// Collects what `Tokenizer` yields for a markup, converted to what `tokenizer` returns.
+        fn collect_tokens(markup: &str) -> (Vec<CurrentState>, Option<String>) {
+            let mut tokens: Vec<CurrentState> = Vec::new();
+            for next_result in Tokenizer::new(markup) {
+                if next_result.is_err() {
+                    return (tokens, Some(format!("{:?}", next_result.unwrap_err())));
+                }
+                tokens.push(CurrentState::from(next_result.unwrap()));
+            }
+            (tokens, None)
+        }
+
+        #[test]
+        /// Both tokenizers must produce the same tokens, with the same spans, and fail the same way.
+        fn test_tokenizer_matches_reference() {
+            let markups = [
+                "   \n\t ",
+                "hello world",
+                "<div>\n  <p id={\"x\"} class=\"a b\">hi {state.name}</p>\n</div>",
+                "<input disabled checked value={state.value} required/>",
+                "<><p>a</p><br></ >",
+                "<Child title=\"x\" on:click={handler}>text</Child>",
+                "<div>hi<!-- <b>{state.x}</b>\n  a < b --><p>{state.y}</p><!----></div>",
+                "<style media=\"all\">.a{color:red}\n</p><b></STYLE ><script></script><p>{x}</p>",
+                "<textarea>\n  <b>{x}</b>\n</textarea><title>a</title>",
+                "<div>\n  <p id=x>hi</p>\n</div>",
+                "<div>\n  <!-- <p>hi</p>\n</div>",
+                "<script>if (a < b) {}",
+                "<p title=\"unterminated>hi</p>",
+                "<p>é ü — 漢字 {\"ß\"}</p>",
+                "<div><p>hi</p x=\"1\"></div>",
+                "<div><p>a</p/></div>",
+                "<p /é>x</p>",
+            ];
+            for markup in markups {
+                assert_eq!(
+                    collect_tokens(markup),
+                    collect_reference_tokens(markup),
+                    "{markup}"
+                );
+            }
+        }
+
+        #[test]
+        /// Tokens must borrow their text from the markup, and props without a value must have none.
+        fn test_tokenizer_borrows_markup() {
+            let markup = "<input disabled value={x}/>";
+            let kinds: Vec<TokenKind> = Tokenizer::new(markup)
+                .map(|next_result| next_result.unwrap().kind)
+                .collect();
+            assert_eq!(
+                kinds,
+                [
+                    TokenKind::OpenAngleBracket,
+                    TokenKind::TagNameOpen("input"),
+                    TokenKind::Prop {
+                        key: "disabled",
+                        value: None
+                    },
+                    TokenKind::Prop {
+                        key: "value",
+                        value: Some("{x}")
+                    },
+                    TokenKind::SelfClosingAngleBracket,
+                ]
+            );
+            if let TokenKind::TagNameOpen(name) = kinds[1] {
+                assert!(markup.as_bytes().as_ptr_range().contains(&name.as_ptr()));
+            }
+        }
+
+        #[test]
+        /// An empty markup has no tokens at all.
+        fn test_tokenizer_empty_markup() {
+            assert!(Tokenizer::new("").next().is_none());
+        }
+
+        #[test]
+        /// A recovering tokenizer must skip malformed attributes and tags, and read the rest as usual;
+        /// an error which reaches the end of the markup must still end the iteration.
+        fn test_tokenizer_recovering() {
+            let markup = "<p id=x class=\"a\">a < b</p><p$ title=\"t\">c</p$><br/><!-- d";
+            let mut kinds: Vec<TokenKind> = Vec::new();
+            let mut error_count = 0;
+            for next_result in Tokenizer::recovering(markup) {
+                if next_result.is_err() {
+                    error_count += 1;
+                } else {
+                    kinds.push(next_result.unwrap().kind);
+                }
+            }
+            assert_eq!(error_count, 5);
+            assert_eq!(
+                kinds,
+                [
+                    TokenKind::OpenAngleBracket,
+                    TokenKind::TagNameOpen("p"),
+                    TokenKind::Prop {
+                        key: "class",
+                        value: Some("\"a\"")
+                    },
+                    TokenKind::CloseAngleBracket,
+                    TokenKind::Text("a "),
+                    TokenKind::OpenAngleBracket,
+                    TokenKind::Text("< b"),
+                    TokenKind::ClosingAngleBracket,
+                    TokenKind::TagNameClose("p"),
+                    TokenKind::CloseAngleBracket,
+                    TokenKind::OpenAngleBracket,
+                    TokenKind::Text("c"),
+                    TokenKind::ClosingAngleBracket,
+                    TokenKind::OpenAngleBracket,
+                    TokenKind::TagNameOpen("br"),
+                    TokenKind::SelfClosingAngleBracket,
+                ]
+            );
+        }
+
+        #[test]
+        /// Recovering from an error right before a multibyte char must not split that char.
+        fn test_tokenizer_recovering_multibyte() {
+            let kinds: Vec<TokenKind> = Tokenizer::recovering("<p /é>x</p>")
+                .filter_map(|next_result| next_result.ok())
+                .map(|token| token.kind)
+                .collect();
+            assert_eq!(
+                kinds,
+                [
+                    TokenKind::OpenAngleBracket,
+                    TokenKind::TagNameOpen("p"),
+                    TokenKind::CloseAngleBracket,
+                    TokenKind::Text("x"),
+                    TokenKind::ClosingAngleBracket,
+                    TokenKind::TagNameClose("p"),
+                    TokenKind::CloseAngleBracket,
+                ]
+            );
+        }
+
+        #[test]
+        /// After an error, the iterator must not yield anything else.
+        fn test_tokenizer_fuses_after_error() {
+            let mut tokens = Tokenizer::new("<p id=x>hi</p>");
+            let error_count = tokens.by_ref().filter(|result| result.is_err()).count();
+            assert_eq!(error_count, 1);
+            assert!(tokens.next().is_none());
+        }
+    }
+}
@@ -0,0 +1,1638 @@
+/// The tokenizer which `Tokenizer` replaced, kept only so that tests and the `tokenizer` bench can
+/// compare the two. It is frozen: fixes go to `Tokenizer`, and this module is only compiled for tests
+/// or with the `legacy-tokenizer` feature.
+pub mod legacy_mod {
+    use crate::{
+        const_util::const_util_mod::{
+            is_input_attribute_key_value_separator, is_input_close_angle_bracket,
+            is_input_close_curly_bracket, is_input_escapable_raw_text_element,
+            is_input_forward_slash, is_input_open_angle_bracket, is_input_open_curly_bracket,
+            is_input_quotation_mark, is_input_raw_text_element, is_input_white_space_alias,
+            ATTRIBUTE_KEY_VALUE_SEPARATOR, BOOLEAN_ATTRIBUTE_VALUE, CLOSING_TAG, COMMENT_END,
+            COMMENT_START, FRAGMENT_TAG_NAME, OPEN_ANGLE_BRACKET, SELF_CLOSING_TAG,
+        },
+        error::error_mod::{locate_error, Error},
+        tokenizer::tokenizer_mod::{is_valid_tag_name, CurrentState, Span, TokenizerState},
+    };
+
+    /// Where a char of the markup is; see `Span`.
+    #[derive(Debug, Clone, Copy)]
+    struct Position {
+        offset: usize,
+        line: usize,
+        column: usize,
+    }
+
+    /// Returns true if the chars of `markup` which start at `index` are `pattern`.
+    fn markup_starts_with(markup: &Vec<char>, index: usize, pattern: &str) -> bool {
+        let mut current = index;
+        for chr in pattern.chars() {
+            if current >= markup.len() || markup[current] != chr {
+                return false;
+            }
+            current += 1;
+        }
+        true
+    }
+
+    /// Reads a comment, like `<!-- a note -->`, given that `index` stands on its `<`. The returned token
+    /// is the content of the comment; nothing inside it, like `<` or `{`, is treated specially.
+    /// This function is responsible for advancing `index` till it reaches the `>` of COMMENT_END.
+    fn read_comment(index: &mut usize, markup: &Vec<char>) -> Result<CurrentState, Error> {
+        let max = markup.len();
+        let mut comment = String::from("");
+        let mut current = *index + COMMENT_START.len();
+        loop {
+            if current >= max {
+                *index = max - 1;
+                return Err(Error::ParsingError(format!(
+                    "A comment was never closed; expected a `{COMMENT_END}` before the end of the presenter."
+                )));
+            }
+            if markup_starts_with(markup, current, COMMENT_END) {
+                *index = current + COMMENT_END.len() - 1;
+                let res = CurrentState {
+                    state: TokenizerState::Comment,
+                    token: comment,
+                    ..Default::default()
+                };
+                return Ok(res);
+            }
+            comment.push(markup[current]);
+            current += 1;
+        }
+    }
+
+    /// Returns true if a closing tag of the raw text element `tag_name`, like `</style>` or `</STYLE >`,
+    /// starts at `index`.
+    fn markup_starts_with_closing_tag(markup: &Vec<char>, index: usize, tag_name: &str) -> bool {
+        if !markup_starts_with(markup, index, CLOSING_TAG) {
+            return false;
+        }
+        let name_start = index + CLOSING_TAG.len();
+        let name_end = name_start + tag_name.chars().count();
+        if name_end >= markup.len() {
+            return false;
+        }
+        let name: String = markup[name_start..name_end].iter().collect();
+        let after_name = markup[name_end];
+        name.eq_ignore_ascii_case(tag_name)
+            && (is_input_close_angle_bracket(after_name)
+                || is_input_forward_slash(&after_name.to_string())
+                || is_input_white_space_alias(after_name.to_string().trim()))
+    }
+
+    /// Reads the content of the raw text element `tag_name`, like `<style>` or `<textarea>`, given that
+    /// `index` stands right after its opening tag. Everything up to its closing tag is returned as a
+    /// `Text` token, as it is; nothing inside it, like `<` or `{`, is treated specially. `None` is
+    /// returned if the element is empty.
+    /// This function is responsible for advancing `index` till it reaches the last char of the content.
+    fn read_raw_text(
+        index: &mut usize,
+        markup: &Vec<char>,
+        tag_name: &str,
+    ) -> Result<Option<CurrentState>, Error> {
+        let max = markup.len();
+        let mut text = String::from("");
+        let mut current = *index;
+        loop {
+            if current >= max {
+                *index = max - 1;
+                return Err(Error::ParsingError(format!(
+                    "`<{tag_name}>` was never closed; expected a `</{tag_name}>` before the end of the presenter."
+                )));
+            }
+            if markup_starts_with_closing_tag(markup, current, tag_name) {
+                break;
+            }
+            text.push(markup[current]);
+            current += 1;
+        }
+        if text.is_empty() {
+            return Ok(None);
+        }
+        *index = current - 1;
+        let res = CurrentState {
+            state: TokenizerState::Text,
+            token: text,
+            ..Default::default()
+        };
+        Ok(Some(res))
+    }
+
+    /// Determines the type of token after encountering a `<` char at uninitialized state, as it can be
+    /// a ClosingTag, an OpenAngleBracket, a Comment or a Text variant. `Ok` variant is returned containing the `CurrentState`
+    /// if nothing goes wrong, `Err` variant explaining why otherwise.
+    /// This function is responsible for advancing `index` till it reaches the char that shows the last
+    /// tokenized char, which is returned in the `token` field.
+    fn get_state_after_open_angle_bracket(
+        text: String,
+        index: &mut usize,
+        markup: &Vec<char>,
+    ) -> Result<CurrentState, Error> {
+        let max = markup.len();
+        if text == "" {
+            if markup_starts_with(markup, *index, COMMENT_START) {
+                return read_comment(index, markup);
+            }
+            let temp = index.clone(); // Cloning index helps us restore to before our assumption about the existence of `/` char.
+            *index += 1;
+            update_starting_tag_index(index, max, markup);
+            let current_string = markup[*index].to_string();
+            let current = current_string.trim();
+            if is_input_forward_slash(current) {
+                let res = CurrentState {
+                    token: CLOSING_TAG.to_owned(),
+                    state: TokenizerState::ClosingAngleBracket,
+                    ..Default::default()
+                };
+                return Ok(res);
+            }
+            *index = temp;
+            let res = CurrentState {
+                state: TokenizerState::OpenAngleBracket,
+                token: OPEN_ANGLE_BRACKET.to_string(),
+                ..Default::default()
+            };
+            return Ok(res);
+        } else {
+            *index -= 1; // We decrement index here because it now stands on `<`, while it should stand on the last index of returned token.
+            let res = CurrentState {
+                state: TokenizerState::Text,
+                token: text,
+                ..Default::default()
+            };
+            return Ok(res);
+        }
+    }
+
+    /// Tokenize `markup` char vector starting from `index` while the current state is uninitialized.
+    /// Uninitialized is used to show one of the below scenarios:
+    /// 1- When tokenization has just started.
+    /// 2- when tokenization has reached one of these states: TokenizerState::CloseAngleBracket,
+    ///    TokenizerState::Text, TokenizerState::Comment, TokenizerState::SelfClosingAngleBracket, and
+    ///    TokenizerState::Uninitialized.
+    ///    This is because tokenization is dealt with in the same manner for all above states.
+    /// `Ok` variant is returned containing the `CurrentState` if nothing goes wrong,
+    /// `Err` variant explaining why otherwise.
+    /// This function is responsible for advancing `index` till it reaches the char that shows the last
+    /// tokenized char, which is returned in the `token` field.
+    fn proceed_from_uninitialized(
+        markup: &Vec<char>,
+        index: &mut usize,
+    ) -> Result<CurrentState, Error> {
+        let max = markup.len();
+        let mut text = String::from("");
+        update_starting_tag_index(index, max, markup);
+        loop {
+            if *index == max {
+                *index -= 1;
+                let state: TokenizerState = if text == "" {
+                    TokenizerState::Finalized
+                } else {
+                    TokenizerState::Text
+                };
+                let res = CurrentState {
+                    state,
+                    token: text.to_owned(),
+                    ..Default::default()
+                };
+                return Ok(res);
+            }
+            let current = markup[*index];
+            if !is_input_open_angle_bracket(current) {
+                text.push(current);
+            } else {
+                return get_state_after_open_angle_bracket(text, index, markup);
+            }
+            *index = *index + 1;
+        }
+    }
+
+    /// Advances `index` till it reaches the first char that doesn't match with `\s`(any whitespace char).
+    /// It will update the `index` mutable reference.
+    fn update_starting_tag_index(index: &mut usize, max: usize, markup: &Vec<char>) {
+        loop {
+            if *index == max {
+                break;
+            }
+            let current_string = markup[*index].to_string();
+            let current = current_string.trim();
+            if !is_input_white_space_alias(current) {
+                break;
+            }
+            *index += 1;
+        }
+    }
+
+    /// Advances `index` to the end of tag name, which ends before a whitespace, `/` or `>`. It will update mutable references of both `index` and
+    /// `tag_name`.This function assumes that `index` currently stands on starting
+    /// character of the tag name, which is a non-whitespace character; so the caller needs to have
+    /// called the `update_starting_tag_index` before calling this function.
+    fn update_starting_tag_name(index: &mut usize, tag_name: &mut String, markup: &Vec<char>) {
+        loop {
+            let current = markup[*index];
+            if !is_input_white_space_alias(&current.to_string().trim())
+                && !is_input_close_angle_bracket(current)
+                && !is_input_forward_slash(&current.to_string())
+            {
+                tag_name.push(current);
+                *index += 1;
+            } else {
+                *index -= 1; // `index` is decremented because we now stand at a whitespace alias char, `/` or `>`; but index must point to the last char of tag's name.
+                break;
+            }
+        }
+    }
+
+    /// Determines the type of token after tag's name is built. Names which start with an uppercase
+    /// letter are components, and must be alphanumeric, since they refer to imported identifiers.
+    /// The closing tag of a component, like `</Card>`, is a TagNameClose like any other closing tag.
+    /// `Ok` variant is returned containing the `CurrentState` if nothing goes wrong,
+    /// `Err` variant explaining why otherwise.
+    fn get_state_after_tag_name(
+        tag_name: String,
+        caller: TokenizerState,
+    ) -> Result<CurrentState, Error> {
+        let collected_tag_name: Vec<char> = tag_name.chars().collect();
+        if collected_tag_name.is_empty() {
+            return Err(Error::ParsingError(
+                "Expected a tag name after the angle bracket, but found none.".to_owned(),
+            ));
+        }
+        let is_component_name = collected_tag_name[0].is_uppercase()
+            && collected_tag_name.iter().all(|x| x.is_alphanumeric());
+        if !is_component_name && !is_valid_tag_name(&tag_name) {
+            let err = Error::ParsingError(format!(
+                "Provided tag name `{tag_name}` contains invalid characters."
+            ));
+            return Err(err);
+        }
+        let first_letter = collected_tag_name[0];
+        let is_uppercase = first_letter.is_uppercase();
+        if is_uppercase && caller != TokenizerState::ClosingAngleBracket {
+            let res = CurrentState {
+                token: tag_name,
+                state: TokenizerState::Component,
+                ..Default::default()
+            };
+            return Ok(res);
+        } else {
+            match caller {
+                TokenizerState::OpenAngleBracket => {
+                    let res = CurrentState {
+                        state: TokenizerState::TagNameOpen,
+                        token: tag_name,
+                        ..Default::default()
+                    };
+                    return Ok(res);
+                }
+                TokenizerState::ClosingAngleBracket => {
+                    let res = CurrentState {
+                        state: TokenizerState::TagNameClose,
+                        token: tag_name,
+                        ..Default::default()
+                    };
+                    return Ok(res);
+                }
+                _ => {
+                    let err = Error::TypeError("`get_state_after_tag_name` shouldn't have been called with this variant of `TokenizerState` as the caller; tokenizer reached a tag name without reaching `<` or `>` first.".to_owned());
+                    return Err(err);
+                }
+            }
+        }
+    }
+
+    /// Tokenize `markup` char vector starting from `index` while the current state is OpenAngleBracket.
+    /// OpenAngleBracket is used to show one of the below scenarios:
+    /// 1- Encountered a '<' char which is a tag's opening; like '<div>' at index 0.
+    /// 2- Encountered a '<' char which is a tag's closing: like '</div>' at index 0.
+    /// Fragments, `<>` and `</>`, are tags whose name is FRAGMENT_TAG_NAME.
+    /// This function is responsible for advancing `index` till it reaches the char that shows the last
+    /// tokenized char, which is returned in the `token` field.
+    /// `Ok` variant is returned containing the `CurrentState` if nothing goes wrong,
+    /// `Err` variant explaining why otherwise.
+    fn proceed_from_open_angle_bracket(
+        markup: &Vec<char>,
+        index: &mut usize,
+        caller: TokenizerState,
+    ) -> Result<CurrentState, Error> {
+        let max = markup.len();
+        let mut tag_name = String::from("");
+
+        update_starting_tag_index(index, max, markup);
+        if *index == max {
+            *index -= 1;
+            let err =
+                Error::ParsingError("No tag name was found after open angle bracket.".to_owned());
+            return Err(err);
+        }
+
+        if is_input_close_angle_bracket(markup[*index]) {
+            *index -= 1; // the name is empty, so `index` must stand right before `>`.
+            let state = if caller == TokenizerState::ClosingAngleBracket {
+                TokenizerState::TagNameClose
+            } else {
+                TokenizerState::TagNameOpen
+            };
+            let res = CurrentState {
+                state,
+                token: FRAGMENT_TAG_NAME.to_owned(),
+                ..Default::default()
+            };
+            return Ok(res);
+        }
+
+        update_starting_tag_name(index, &mut tag_name, markup);
+        return get_state_after_tag_name(tag_name, caller);
+    }
+
+    /// This function returns a String which is supposed to be a key for a key-value pair of props
+    /// or attributes like `alt={"This is an image"}`, or the name of a boolean attribute like
+    /// `disabled`, which has no value. Whitespace between the key and PROP_KEY_VALUE_SEPARATOR is
+    /// skipped; the separator is a part of the returned key, so a key without it is a boolean attribute.
+    /// This function is responsible for advancing `index` till it reaches the char that shows the last
+    /// tokenized char, which in this context, is supposed to be PROP_KEY_VALUE_SEPARATOR; or the last
+    /// char of the key, if it has no value.
+    fn read_key_of_prop(index: &mut usize, markup: &Vec<char>) -> Result<String, Error> {
+        let max = markup.len();
+        let mut key = String::from("");
+        loop {
+            if *index == max {
+                *index -= 1;
+                return Err(Error::ParsingError(
+                    "Expected a key-value pair, but reached the end of markup.".to_owned(),
+                ));
+            }
+            let current = markup[*index];
+            let current_string = current.to_string();
+            if is_input_white_space_alias(current_string.trim()) {
+                let mut next_index = *index;
+                update_starting_tag_index(&mut next_index, max, markup);
+                if next_index == max {
+                    *index = max - 1;
+                    return Err(Error::ParsingError(
+                        "Expected a key-value pair, but reached the end of markup.".to_owned(),
+                    ));
+                }
+                if !is_input_attribute_key_value_separator(&markup[next_index].to_string()) {
+                    *index -= 1; // the key has no value; `index` must point to its last char.
+                    break;
+                }
+                *index = next_index;
+                continue;
+            }
+            if is_input_close_angle_bracket(current) || is_input_forward_slash(&current_string) {
+                *index -= 1; // the key has no value; `index` must point to its last char.
+                break;
+            }
+            key.push(current);
+            if is_input_attribute_key_value_separator(&current_string) {
+                break;
+            }
+            *index += 1;
+        }
+        if key.is_empty() || key == ATTRIBUTE_KEY_VALUE_SEPARATOR {
+            return Err(Error::ParsingError(
+                "Expected the name of an attribute, but found none.".to_owned(),
+            ));
+        }
+        return Ok(key);
+    }
+
+    /// Reads a value which is wrapped in quotation marks, like `"btn"` or `'btn'`, starting from the
+    /// opening quotation mark at `index`. Nothing inside the quotation marks is treated specially.
+    /// This function is responsible for advancing `index` till it reaches the closing quotation mark.
+    fn read_quoted_value_of_prop(index: &mut usize, markup: &Vec<char>) -> Result<String, Error> {
+        let max = markup.len();
+        let quote = markup[*index];
+        let mut value = quote.to_string();
+        loop {
+            *index += 1;
+            if *index == max {
+                *index -= 1;
+                return Err(Error::ParsingError(format!(
+                    "Expected a closing {quote} for the value of an attribute, but reached the end of markup."
+                )));
+            }
+            let current = markup[*index];
+            value.push(current);
+            if current == quote {
+                return Ok(value);
+            }
+        }
+    }
+
+    /// Returns `Ok` variant containing a String which is supposed to be the value for
+    /// key-value pair of props or attributes like `alt={"This is an image"}` or `alt="This is an image"`.
+    /// `Err` variant is returned in case of errors.
+    /// This function is responsible for advancing `index` till it reaches the char that shows the last
+    /// tokenized char, which in this context, is supposed to be CLOSE_CURLY_BRACKET or the closing
+    /// quotation mark.
+    /// Curly brackets inside JS strings of the value, like `{"a } b"}` or `{"{{state}}"}`, don't
+    /// close it.
+    fn read_value_of_prop(index: &mut usize, markup: &Vec<char>) -> Result<String, Error> {
+        let max = markup.len();
+        update_starting_tag_index(index, max, markup);
+        if *index == max {
+            *index -= 1;
+            return Err(Error::ParsingError(
+                "Expected a key-value pair, but reached the end of markup.".to_owned(),
+            ));
+        }
+
+        let value_wrapper = markup[*index];
+        if is_input_quotation_mark(value_wrapper) {
+            return read_quoted_value_of_prop(index, markup);
+        }
+        if !is_input_open_curly_bracket(value_wrapper) {
+            let err = Error::ParsingError(format!("Value of props and attributes must be wrapped around curly brackets or quotation marks. Provided char was {value_wrapper}"));
+            return Err(err);
+        }
+        let mut value = String::from("");
+        let mut wrapper_stack: Vec<String> = Vec::new();
+        let mut open_quote: Option<char> = None; // the quotation mark of the JS string we are in, if any
+        loop {
+            if *index == max {
+                *index -= 1;
+                return Err(Error::ParsingError(
+                    "Expected a key-value pair, but reached the end of markup.".to_owned(),
+                ));
+            }
+
+            let current = markup[*index];
+            value.push_str(&current.to_string());
+            if open_quote.is_some() {
+                if current == '\\' && *index + 1 < max {
+                    *index += 1;
+                    value.push(markup[*index]);
+                } else if Some(current) == open_quote {
+                    open_quote = None;
+                }
+            } else if is_input_quotation_mark(current) || current == '`' {
+                open_quote = Some(current);
+            } else if is_input_open_curly_bracket(current) {
+                wrapper_stack.push(current.to_string());
+            } else if is_input_close_curly_bracket(current) {
+                wrapper_stack.pop();
+            }
+            if wrapper_stack.is_empty() {
+                break;
+            }
+
+            *index += 1;
+        }
+        if !wrapper_stack.is_empty() {
+            let err = Error::ParsingError("Could not parse props/attributes properly. You have probably messed up with some curly brackets.".to_owned());
+            return Err(err);
+        }
+        Ok(value)
+    }
+
+    // todo: add max to functions' parameter list
+
+    /// Determines if the encountered `/` char is valid or not.
+    /// `Ok` variant is returned containing the `CurrentState` if nothing goes wrong,
+    /// `Err` variant explaining why otherwise.
+    fn get_state_after_slash(
+        index: &mut usize,
+        markup: &Vec<char>,
+        max: usize,
+    ) -> Result<CurrentState, Error> {
+        *index += 1; // we want to check if the char after `/` is `>` or not, so we must advance
+                     // the index by one; otherwise, call to `update_starting_tag_index` won't advance the index
+                     // because it currently stands at a non-whitespace char(/).
+        update_starting_tag_index(index, max, markup);
+        let has_closing_angle_bracket = markup[*index] == '>';
+        if has_closing_angle_bracket {
+            let res = CurrentState {
+                state: TokenizerState::SelfClosingAngleBracket,
+                token: SELF_CLOSING_TAG.to_owned(),
+                ..Default::default()
+            };
+            return Ok(res);
+        } else {
+            let err = Error::ParsingError(
+                "Expected a closing angle bracket, but did not find it.".to_owned(),
+            );
+            return Err(err);
+        }
+    }
+
+    /// Returns an `Ok` including pair of props if its format is correct, `Err` otherwise.
+    /// Currently, the acceptable prop format is `key={"value"}`, `key={'value'}`, `key={js expression}`,
+    /// `key="value"` and `key='value'`. A boolean attribute without a value, like `disabled`, becomes
+    /// `disabled={true}`, so the token always has the `key=value` format.
+    fn get_state_from_props(index: &mut usize, markup: &Vec<char>) -> Result<CurrentState, Error> {
+        let key_result = read_key_of_prop(index, markup);
+        if key_result.is_err() {
+            return Err(key_result.unwrap_err());
+        }
+        let key = key_result.unwrap();
+        if !key.ends_with(ATTRIBUTE_KEY_VALUE_SEPARATOR) {
+            let res = CurrentState {
+                state: TokenizerState::Props,
+                token: key + ATTRIBUTE_KEY_VALUE_SEPARATOR + BOOLEAN_ATTRIBUTE_VALUE,
+                ..Default::default()
+            };
+            return Ok(res);
+        }
+        *index += 1; // This is for PROP_KEY_VALUE_SEPARATOR
+        let value_result = read_value_of_prop(index, markup);
+        if value_result.is_err() {
+            return Err(value_result.unwrap_err());
+        }
+        let value = value_result.unwrap();
+        let key_value_pair = key + &value;
+
+        if key_value_pair == "" {
+            let err = Error::ParsingError(
+                "This should not have happened. A value was supposed to exist, but it didn't."
+                    .to_owned(),
+            );
+            return Err(err);
+        } else {
+            let res = CurrentState {
+                state: TokenizerState::Props,
+                token: key_value_pair,
+                ..Default::default()
+            };
+            return Ok(res);
+        }
+    }
+
+    /// Tokenize `markup` char vector starting from `index` while the current state is Tag, Component or Prop.
+    /// This is because after a tag name or component name or even a pair of props, we expect the same set
+    /// of tokens to appear; which are CloseAngleBracket, SelfClosingAngleBracket and a pair of Props
+    /// This function is responsible for advancing `index` till it reaches the char that shows the last
+    /// tokenized char, which is returned in the `token` field.
+    /// `Ok` variant is returned containing the `CurrentState` if nothing goes wrong,
+    /// `Err` variant explaining why otherwise.
+    fn proceed_from_name(markup: &Vec<char>, index: &mut usize) -> Result<CurrentState, Error> {
+        let max = markup.len();
+        update_starting_tag_index(index, max, markup);
+        if markup[*index] == '>' {
+            let res = CurrentState {
+                token: ">".to_owned(),
+                state: TokenizerState::CloseAngleBracket,
+                ..Default::default()
+            };
+            return Ok(res);
+        } else if markup[*index] == '/' {
+            return get_state_after_slash(index, markup, max);
+        } else {
+            return get_state_from_props(index, markup);
+        }
+    }
+
+    /// Tokenize `markup` char vector starting from `index` while the current state is TagNameClose.
+    /// Closing tags can't have props or `/`, so only a CloseAngleBracket may come after their name;
+    /// anything else is an `Err` variant naming the closing tag, `tag_name`.
+    fn proceed_from_closing_tag_name(
+        markup: &Vec<char>,
+        index: &mut usize,
+        tag_name: &str,
+    ) -> Result<CurrentState, Error> {
+        let max = markup.len();
+        update_starting_tag_index(index, max, markup);
+        if *index == max {
+            let msg = "Expected a closing angle bracket, but reached the end of markup.".to_owned();
+            return Err(Error::ParsingError(msg));
+        }
+        let current = markup[*index];
+        if !is_input_close_angle_bracket(current) {
+            let msg = format!("Expected `>` to end the closing tag `</{tag_name}>`, but found `{current}`; closing tags can't have attributes or `/`.");
+            return Err(Error::ParsingError(msg));
+        }
+        let res = CurrentState {
+            token: ">".to_owned(),
+            state: TokenizerState::CloseAngleBracket,
+            ..Default::default()
+        };
+        Ok(res)
+    }
+
+    /// Returns the byte offset, line and column of every char of `markup`, plus the position right after
+    /// its last char, so spans can be looked up by char index.
+    fn get_positions(markup: &Vec<char>) -> Vec<Position> {
+        let mut positions: Vec<Position> = Vec::with_capacity(markup.len() + 1);
+        let mut position = Position {
+            offset: 0,
+            line: 1,
+            column: 1,
+        };
+        for current in markup {
+            positions.push(position);
+            position.offset += current.len_utf8();
+            if *current == '\n' {
+                position.line += 1;
+                position.column = 1;
+            } else {
+                position.column += 1;
+            }
+        }
+        positions.push(position);
+        positions
+    }
+
+    /// Returns the span which starts at the char at `start` and ends right before the char at `end`.
+    fn get_span(positions: &Vec<Position>, start: usize, end: usize) -> Span {
+        let Position {
+            offset,
+            line,
+            column,
+        } = positions[start];
+        Span {
+            start: offset,
+            end: positions[end.max(start)].offset,
+            line,
+            column,
+        }
+    }
+
+    /// Given a markup, returns a closure which returns the next token of it, along with its span, on
+    /// each call. Once the markup is exhausted, every call returns a `Finalized` token. Errors are
+    /// located in `markup`, starting from where the failed token would have started.
+    pub fn tokenizer(markup: String) -> impl FnMut() -> Result<CurrentState, Error> {
+        let mut current_index: usize = 0;
+        let collected_markup: Vec<char> = markup.chars().collect();
+        let positions = get_positions(&collected_markup);
+        let max = collected_markup.len();
+        let mut state: TokenizerState = TokenizerState::Uninitialized;
+        // name of the raw text element, like `style`, whose opening tag is being read
+        let mut raw_text_element: Option<String> = None;
+        let mut closing_tag_name = String::new(); // name of the closing tag which is being read
+        let next = move || {
+            let mut start_index = current_index.min(max);
+            update_starting_tag_index(&mut start_index, max, &collected_markup);
+            let mut raw_text_result: Result<Option<CurrentState>, Error> = Ok(None);
+            if state == TokenizerState::CloseAngleBracket && raw_text_element.is_some() {
+                let tag_name = raw_text_element.take().unwrap();
+                raw_text_result = read_raw_text(&mut current_index, &collected_markup, &tag_name);
+            }
+            let current_state_result = match state {
+                _ if raw_text_result.is_err() => Err(raw_text_result.unwrap_err()),
+                _ if raw_text_result.as_ref().unwrap().is_some() => {
+                    Ok(raw_text_result.unwrap().unwrap())
+                }
+                TokenizerState::Uninitialized
+                | TokenizerState::SelfClosingAngleBracket
+                | TokenizerState::CloseAngleBracket
+                | TokenizerState::Text
+                | TokenizerState::Comment => {
+                    proceed_from_uninitialized(&collected_markup, &mut current_index)
+                }
+                TokenizerState::OpenAngleBracket | TokenizerState::ClosingAngleBracket => {
+                    proceed_from_open_angle_bracket(
+                        &collected_markup,
+                        &mut current_index,
+                        state.clone(),
+                    )
+                }
+                TokenizerState::TagNameClose => proceed_from_closing_tag_name(
+                    &collected_markup,
+                    &mut current_index,
+                    &closing_tag_name,
+                ),
+                TokenizerState::TagNameOpen | TokenizerState::Component | TokenizerState::Props => {
+                    proceed_from_name(&collected_markup, &mut current_index)
+                }
+                TokenizerState::Finalized => {
+                    return Ok(CurrentState {
+                        token: "".to_owned(),
+                        state: state.clone(),
+                        span: get_span(&positions, max, max),
+                    })
+                }
+            };
+            if current_state_result.is_err() {
+                let end_index = (current_index + 1).min(max);
+                let span = get_span(&positions, start_index, end_index);
+                return Err(locate_error(
+                    current_state_result.unwrap_err(),
+                    &span,
+                    &markup,
+                ));
+            }
+            let CurrentState {
+                state: state_,
+                token,
+                ..
+            } = current_state_result.unwrap();
+            state = state_;
+            match state {
+                TokenizerState::TagNameOpen
+                    if is_input_raw_text_element(&token)
+                        || is_input_escapable_raw_text_element(&token) =>
+                {
+                    raw_text_element = Some(token.to_owned());
+                }
+                TokenizerState::TagNameOpen
+                | TokenizerState::Component
+                | TokenizerState::SelfClosingAngleBracket => raw_text_element = None,
+                TokenizerState::TagNameClose => closing_tag_name = token.to_owned(),
+                _ => {}
+            }
+            current_index += 1;
+            let res = CurrentState {
+                token,
+                state: state.clone(),
+                span: get_span(&positions, start_index, current_index.min(max)),
+            };
+            Ok(res)
+        };
+
+        next
+    }
+
+    #[cfg(test)]
+
+    /// Test module for `tokenizer` module's functionality.
+    ///
+    ///
+    /// Note that it is preferred to have tests and functionality in separate modules; however, this
+    /// would require to publicly interface ALL functionality of a module, which is not desired.
+    mod tests {
+
+        use super::*;
+        use crate::error::error_mod::Error;
+
+        #[test]
+        /// An empty markup, which is any markup that has no char other than whitespace, should
+        /// make tokenization state to `Finalized`.
+        fn test_empty_markup() {
+            let markup: Vec<char> = "          \n   \t  \n".chars().collect();
+            let mut index = 0usize;
+            let CurrentState { state, token, .. } =
+                proceed_from_uninitialized(&markup, &mut index).unwrap();
+            match state {
+                TokenizerState::Finalized => {
+                    assert!(token == "" && index == markup.len() - 1)
+                }
+                _ => {
+                    assert!(false)
+                }
+            }
+        }
+
+        #[test]
+        fn test_text_markup() {
+            let markup_string = "This is a plain test";
+            let markup: Vec<char> = markup_string.chars().collect();
+            let mut index = 0usize;
+            let CurrentState { state, token, .. } =
+                proceed_from_uninitialized(&markup, &mut index).unwrap();
+            match state {
+                TokenizerState::Text => {
+                    assert!(token == markup_string && index == markup.len() - 1)
+                }
+                _ => {
+                    assert!(false)
+                }
+            }
+        }
+
+        #[test]
+        /// When at `CurrentState::Uninitialized` and the next non-whitespace char is an open angle
+        /// bracket, `CurrentState::OpenAngleBracket` must be the new state; index should be equal to
+        /// index of open angle bracket char.
+        fn test_open_angle_bracket() {
+            let markup_string = "    <div";
+            let markup: Vec<char> = markup_string.chars().collect();
+            let mut index = 0usize;
+            let CurrentState { state, token, .. } =
+                proceed_from_uninitialized(&markup, &mut index).unwrap();
+            match state {
+                TokenizerState::OpenAngleBracket => {
+                    assert!(token == "<" && index == 4)
+                }
+                _ => {
+                    assert!(false)
+                }
+            }
+        }
+
+        #[test]
+        /// When at `CurrentState::Uninitialized` and the next non-whitespace char is an open angle
+        /// bracket, `CurrentState::ClosingAngleBracket` must be the new state if there is a `/` char
+        /// regardless of non-whitespace chars after it; index should be equal to index of `/` char.
+        fn test_closing_angle_bracket() {
+            let markup_string = "<div>hi<   /  div>";
+            let markup: Vec<char> = markup_string.chars().collect();
+            let mut index = 7usize;
+            let CurrentState { state, token, .. } =
+                proceed_from_uninitialized(&markup, &mut index).unwrap();
+            match state {
+                TokenizerState::ClosingAngleBracket => {
+                    assert!(token == "</" && index == 11)
+                }
+                _ => {
+                    assert!(false)
+                }
+            }
+        }
+
+        #[test]
+        /// When at `CurrentState::CloseAngleBracket` and the next non-whitespace char is not an open angle
+        /// bracket, `CurrentState::Text` must be the new state; index should be equal to
+        /// index of text's last char.
+        fn test_text_inside_tag() {
+            let markup_string = "<div>hello world<div>hi</div></div>";
+            let markup: Vec<char> = markup_string.chars().collect();
+            let mut index = 5usize;
+            let CurrentState { state, token, .. } =
+                proceed_from_uninitialized(&markup, &mut index).unwrap();
+            match state {
+                TokenizerState::Text => {
+                    assert!(token == "hello world" && index == 15)
+                }
+                _ => {
+                    assert!(false)
+                }
+            }
+        }
+
+        #[test]
+        /// The `update_starting_tag_index` should advance the `index` mutable reference to the first
+        /// character which is not a whitespace character.
+        fn test_update_starting_tag_index_from_whitespace() {
+            let markup_string = "<div    \n\t   ></div>";
+            let markup: Vec<char> = markup_string.chars().collect();
+            let mut index = 4usize;
+            update_starting_tag_index(&mut index, markup.len(), &markup);
+            assert_eq!(index, 13);
+        }
+
+        #[test]
+        /// The `update_starting_tag_index` should advance the `index` mutable reference to the first
+        /// character which is not a whitespace character; so if `index` is already pointing to a
+        /// non-whitespace character, it should not be advanced.
+        fn test_update_starting_tag_index_from_non_whitespace() {
+            let markup_string = "<div    \n\t   ></div>";
+            let markup: Vec<char> = markup_string.chars().collect();
+            let mut index = 3usize;
+            update_starting_tag_index(&mut index, markup.len(), &markup);
+            assert_eq!(index, 3);
+        }
+
+        #[test]
+        /// the `update_starting_tag_index` should not advance the `index` to illegal state,
+        /// which is more than length of markup vector.
+        fn test_update_starting_tag_index_from_last_char() {
+            let markup_string = "<div></div>";
+            let markup: Vec<char> = markup_string.chars().collect();
+            let max = markup.len();
+            let mut index = max as usize;
+            update_starting_tag_index(&mut index, max, &markup);
+            assert_eq!(index, max);
+        }
+
+        #[test]
+        /// When index is pointing to the first character of tag name, calling `update_starting_tag_name`
+        /// must advance the mutable reference of `index` to the position of tag name's last character.
+        fn test_update_starting_tag_name() {
+            let markup_arr = vec![
+                "<p id={\"hi\"}></p>".to_owned(),
+                "<p></p>".to_owned(),
+                "<p ></p>".to_owned(),
+            ];
+            let mut res: Vec<bool> = Vec::new();
+            for markup_string in markup_arr {
+                let markup: Vec<char> = markup_string.chars().collect();
+                let mut index = 1usize;
+                let mut tag_name: String = String::new();
+                update_starting_tag_name(&mut index, &mut tag_name, &markup);
+                res.push(index == 1 && tag_name == "p");
+            }
+            assert!(res.iter().all(|r| *r))
+        }
+
+        #[test]
+        /// `get_state_after_tag_name` validates the argument which is passed to it as `tag_name`
+        /// parameter and will return error if `tag_name` is not alphanumerical.
+        fn test_get_state_after_tag_name_illegal_character() {
+            let tag_name = String::from("article<");
+            let result = get_state_after_tag_name(tag_name, TokenizerState::OpenAngleBracket);
+            if result.is_err() {
+                assert!(matches!(result.unwrap_err(), Error::ParsingError(_)));
+            } else {
+                assert!(false);
+            }
+        }
+
+        #[test]
+        /// Custom element names and namespaced names must be accepted as tag names, as long as they
+        /// are well-formed.
+        fn test_get_state_after_tag_name_custom_and_namespaced() {
+            for tag_name in [
+                "my-widget",
+                "x-1.2_b",
+                "svg:feGaussianBlur",
+                "feGaussianBlur",
+            ] {
+                let result =
+                    get_state_after_tag_name(tag_name.to_owned(), TokenizerState::OpenAngleBracket);
+                assert!(
+                    matches!(result, Ok(CurrentState { state: TokenizerState::TagNameOpen, token, .. }) if token == tag_name)
+                );
+            }
+            for tag_name in [
+                "my-Widget",
+                "-widget",
+                "svg:",
+                ":rect",
+                "a:b:c",
+                "My-Widget",
+            ] {
+                let result =
+                    get_state_after_tag_name(tag_name.to_owned(), TokenizerState::OpenAngleBracket);
+                assert!(matches!(result, Err(Error::ParsingError(_))), "{tag_name}");
+            }
+        }
+
+        #[test]
+        /// A `/` right after a tag name ends the name, so `<br/>` is a self-closing `br`.
+        fn test_update_starting_tag_name_before_slash() {
+            let markup: Vec<char> = "<br/>".chars().collect();
+            let mut index = 1usize;
+            let mut tag_name: String = String::new();
+            update_starting_tag_name(&mut index, &mut tag_name, &markup);
+            assert!(index == 2 && tag_name == "br");
+        }
+
+        #[test]
+        /// `get_state_after_tag_name` must return a `TokenizerState::Component` variant when
+        /// provided `tag_name` starts with a uppercase character.
+        fn test_get_state_after_tag_name_with_component() {
+            let tag_name = String::from("TableRow");
+            let result =
+                get_state_after_tag_name(tag_name.clone(), TokenizerState::OpenAngleBracket);
+            if result.is_ok() {
+                let CurrentState { state, token, .. } = result.unwrap();
+                assert!(matches!(state, TokenizerState::Component) && token == tag_name);
+            } else {
+                assert!(false);
+            }
+        }
+
+        #[test]
+        /// The name of a component in a closing tag must be a `TokenizerState::TagNameClose`.
+        fn test_get_state_after_tag_name_with_closing_component() {
+            let result = get_state_after_tag_name(
+                String::from("TableRow"),
+                TokenizerState::ClosingAngleBracket,
+            );
+            assert!(
+                matches!(result, Ok(CurrentState { state: TokenizerState::TagNameClose, token, .. }) if token == "TableRow")
+            );
+        }
+
+        #[test]
+        /// `get_state_after_tag_name` must return a `TokenizerState::TagNameOpen` variant when
+        /// current state is `TokenizerState::OpenAngleBracket`
+        fn test_get_state_after_tag_name_open() {
+            let tag_name = String::from("span");
+            let result =
+                get_state_after_tag_name(tag_name.clone(), TokenizerState::OpenAngleBracket);
+            if result.is_ok() {
+                let CurrentState { state, token, .. } = result.unwrap();
+                assert!(matches!(state, TokenizerState::TagNameOpen) && token == tag_name);
+            } else {
+                assert!(false);
+            }
+        }
+
+        #[test]
+        /// `get_state_after_tag_name` must return a `TokenizerState::TagNameClose` variant when
+        /// current state is `TokenizerState::ClosingAngleBracket`
+        fn test_get_state_after_tag_name_close() {
+            let tag_name = String::from("span");
+            let result =
+                get_state_after_tag_name(tag_name.clone(), TokenizerState::ClosingAngleBracket);
+            if result.is_ok() {
+                let CurrentState { state, token, .. } = result.unwrap();
+                assert!(matches!(state, TokenizerState::TagNameClose) && token == tag_name);
+            } else {
+                assert!(false);
+            }
+        }
+
+        #[test]
+        /// `get_state_after_tag_name` must return an error if it was called from any `TokenizerState`
+        /// other than `TokenizerState::ClosingAngleBracket` and `TokenizerState::OpenAngleBracket`.
+        fn test_get_state_after_tag_name_invalid_caller() {
+            let tag_name = String::from("span");
+            let invalid_callers: Vec<TokenizerState> = vec![
+                TokenizerState::Uninitialized,
+                TokenizerState::SelfClosingAngleBracket,
+                TokenizerState::TagNameOpen,
+                TokenizerState::TagNameClose,
+                TokenizerState::Component,
+                TokenizerState::Props,
+                TokenizerState::Text,
+                TokenizerState::Finalized,
+            ];
+            for caller in invalid_callers {
+                let result = get_state_after_tag_name(tag_name.clone(), caller);
+                if result.is_err() {
+                    assert!(matches!(result.unwrap_err(), Error::TypeError(_)));
+                } else {
+                    assert!(false);
+                }
+            }
+        }
+
+        #[test]
+        /// `proceed_from_open_angle_bracket` should return an `Err` variant if there is no non-empty character
+        /// after `<`.
+        ///
+        /// Note that this is the only scenario which is checked for `proceed_from_open_angle_bracket`, because
+        /// rest of its logic is basically tested. See test cases for `update_starting_tag_index`,
+        /// `get_state_after_tag_name` and `update_starting_tag_name`.
+        fn test_proceed_from_open_angle_bracket_empty() {
+            let markup_string = "< ";
+            let markup: Vec<char> = markup_string.chars().collect();
+            let mut index = 1usize;
+            let proceed_from_open_angle_bracket_result = proceed_from_open_angle_bracket(
+                &markup,
+                &mut index,
+                TokenizerState::OpenAngleBracket,
+            );
+            assert!(matches!(
+                proceed_from_open_angle_bracket_result,
+                Result::Err(err) if matches!(err, Error::ParsingError(_))
+            ))
+        }
+
+        #[test]
+        fn test_read_key_of_prop_invalid() {
+            let markup_string = "<div id";
+            let markup: Vec<char> = markup_string.chars().collect();
+            let mut index = 5usize;
+            let read_key_of_prop_result = read_key_of_prop(&mut index, &markup);
+            assert!(
+                matches!(read_key_of_prop_result, Err(err) if matches!(err, Error::ParsingError(_)))
+            )
+        }
+
+        #[test]
+        fn test_read_key_of_prop_valid() {
+            let markup_string = "<div id={\"hi\"}></div>";
+            let markup: Vec<char> = markup_string.chars().collect();
+            let mut index = 5usize;
+            let key = read_key_of_prop(&mut index, &markup).unwrap();
+            assert!(key == "id=" && index == 7);
+        }
+
+        #[test]
+        /// `read_value_of_prop` expects `index` to point to a `{` character or a quotation mark(after ignoring
+        /// whitespace characters); It must return an `Err` if it's not found.
+        fn test_read_value_of_prop_invalid_wrapper() {
+            let markup_string = "<div id=hi></div>";
+            let markup: Vec<char> = markup_string.chars().collect();
+            let mut index = 8usize;
+            let read_value_of_prop_result = read_value_of_prop(&mut index, &markup);
+            assert!(
+                matches!(read_value_of_prop_result, Err(err) if matches!(err, Error::ParsingError(_)))
+            );
+        }
+
+        #[test]
+        /// `read_value_of_prop` must return an `Ok` variant when it encounters a value of prop/attribute;
+        /// furthermore, it should update the `index` to point to `}` character.
+        fn test_read_value_of_prop() {
+            let markup_string = "<div id={\"hi\"}></div>";
+            let markup: Vec<char> = markup_string.chars().collect();
+            let mut index = 8usize;
+            let read_value_of_prop_result = read_value_of_prop(&mut index, &markup);
+            if read_value_of_prop_result.is_ok() {
+                let read_value_of_prop = read_value_of_prop_result.unwrap();
+                assert_eq!(read_value_of_prop, "{\"hi\"}");
+                assert_eq!(index, 13);
+            } else {
+                assert!(false);
+            }
+        }
+
+        #[test]
+        /// `read_value_of_prop` must read values wrapped in quotation marks as they are, up to the
+        /// matching quotation mark; `index` must point to it afterwards.
+        fn test_read_value_of_prop_quoted() {
+            let markup_string = "<div id='say \"{hi}\"' class=\"btn\"></div>";
+            let markup: Vec<char> = markup_string.chars().collect();
+            let mut index = 8usize;
+            let read_value_of_prop_result = read_value_of_prop(&mut index, &markup);
+            assert!(matches!(read_value_of_prop_result, Ok(value) if value == "'say \"{hi}\"'"));
+            assert_eq!(index, 19);
+            let mut index = 27usize;
+            let read_value_of_prop_result = read_value_of_prop(&mut index, &markup);
+            assert!(matches!(read_value_of_prop_result, Ok(value) if value == "\"btn\""));
+        }
+
+        #[test]
+        /// An unterminated quoted value must be reported.
+        fn test_read_value_of_prop_unterminated_quote() {
+            let markup: Vec<char> = "<div id=\"hi></div>".chars().collect();
+            let mut index = 8usize;
+            let read_value_of_prop_result = read_value_of_prop(&mut index, &markup);
+            assert!(matches!(
+                read_value_of_prop_result,
+                Err(Error::ParsingError(_))
+            ));
+        }
+
+        #[test]
+        /// Attributes without a value are boolean attributes, whether they are followed by another
+        /// attribute, `/>` or `>`.
+        fn test_get_state_from_props_boolean() {
+            let markup_string = "<input disabled checked/><input required  >";
+            let markup: Vec<char> = markup_string.chars().collect();
+            for (start, expected_token, expected_index) in [
+                (7usize, "disabled={true}", 14usize),
+                (16, "checked={true}", 22),
+                (32, "required={true}", 39),
+            ] {
+                let mut index = start;
+                let prop_result = get_state_from_props(&mut index, &markup);
+                assert!(
+                    matches!(prop_result, Ok(CurrentState { state: TokenizerState::Props, token, .. }) if token == expected_token)
+                );
+                assert_eq!(index, expected_index);
+            }
+        }
+
+        #[test]
+        /// Curly brackets inside strings, including escaped ones like `}}`, must not end the value.
+        fn test_read_value_of_prop_brackets_in_strings() {
+            let markup_string = "<p title={\"}} {state.a} \\\" {{\"} id={'}'}></p>";
+            let markup: Vec<char> = markup_string.chars().collect();
+            let mut index = 9usize;
+            let read_value_of_prop_result = read_value_of_prop(&mut index, &markup);
+            assert!(
+                matches!(read_value_of_prop_result, Ok(value) if value == "{\"}} {state.a} \\\" {{\"}")
+            );
+            assert_eq!(markup[index + 1], ' ');
+        }
+
+        #[test]
+        /// `read_value_of_prop` must return an `Err` variant when bracket sequence encounters more `{` than
+        /// `}`. Note that the other way around is not handled by this function and is left off to the next
+        /// state handler.
+        fn test_read_value_of_prop_invalid_bracket_sequence() {
+            let markup_string = "<div id={{\"hi\"}></div>";
+            let markup: Vec<char> = markup_string.chars().collect();
+            let mut index = 8usize;
+            let read_value_of_prop_result = read_value_of_prop(&mut index, &markup);
+            assert!(
+                matches!(read_value_of_prop_result, Err(err) if matches!(err, Error::ParsingError(_)))
+            )
+        }
+
+        #[test]
+        /// `get_state_after_slash` should return an `Ok` variant if it encounters a `>` after observing a
+        /// `/` character.
+        fn test_get_state_after_slash_valid() {
+            let markup_string = "<img / >";
+            let markup: Vec<char> = markup_string.chars().collect();
+            let mut index = 5usize;
+            let max = markup.len();
+            let get_state_after_slash_result = get_state_after_slash(&mut index, &markup, max);
+            assert!(
+                matches!(get_state_after_slash_result, Ok(val) if matches!(val.state, TokenizerState::SelfClosingAngleBracket) && matches!(val.token.as_str(), "/>"))
+            )
+        }
+
+        #[test]
+        /// `get_state_after_slash` must return an `Err` variant if no `>` is found after a `/` character.
+        fn test_get_state_after_slash_invalid() {
+            let markup_string = "<img / <p>hi</p>";
+            let markup: Vec<char> = markup_string.chars().collect();
+            let mut index = 5usize;
+            let max = markup.len();
+            let get_state_after_slash_result = get_state_after_slash(&mut index, &markup, max);
+            assert!(
+                matches!(get_state_after_slash_result, Err(err) if matches!(err, Error::ParsingError(_)))
+            )
+        }
+
+        #[test]
+        /// `get_state_from_props` is essentially a wrapper for `read_key_of_prop` and `read_value_of_prop`,
+        /// so there is no further point in testing it thoroughly.
+        fn test_get_state_from_props() {
+            let markup_string = "<img src = {state.src}/>";
+            let markup: Vec<char> = markup_string.chars().collect();
+            let mut index = 5usize;
+            let prop_result = get_state_from_props(&mut index, &markup);
+            if prop_result.is_ok() {
+                let CurrentState { state, token, .. } = prop_result.unwrap();
+                assert!(matches!(state, TokenizerState::Props));
+                assert_eq!(token, "src={state.src}");
+            } else {
+                assert!(false);
+            }
+        }
+
+        #[test]
+        /// `proceed_from_name` should return an `Ok` which contains a `>` character when reached one
+        /// after a tag's name.
+        ///
+        /// Note that other paths of `proceed_from_name` are covered in previous tests.
+        fn test_proceed_from_name() {
+            let markup_string = "<h2>Hello world</h2  >";
+            let markup: Vec<char> = markup_string.chars().collect();
+            let mut index = 19usize;
+            let proceed_from_name_result = proceed_from_name(&markup, &mut index);
+            assert!(
+                matches!(proceed_from_name_result, Ok(val) if matches!(val.state, TokenizerState::CloseAngleBracket) && matches!(val.token.as_str(), ">"))
+            );
+        }
+
+        // TODO: add tests for the runner every now and then.
+        #[test]
+        fn tokenizer_test_1() {
+            let markup = String::from(
+                "  <div>
+            <h2>{\"User: {username}\"}</h2>
+            <p>{\"Age: {age}\"}</p>
+            <img src={\"https://example.com/{username}\"} alt={\"Profile picture of {username}\"} />
+            <a href={\"https://example.com/{username}\"} target={\"_blank\"}>View Profile</a>
+          </div>",
+            );
+            let mut generator = tokenizer(markup);
+            loop {
+                let generator_res = generator();
+                assert!(!matches!(generator_res, Err(_)));
+                let CurrentState {
+                    state,
+                    token: _token,
+                    ..
+                } = generator_res.unwrap();
+                if matches!(state, TokenizerState::Finalized) {
+                    break;
+                }
+            }
+        }
+
+        #[test]
+        fn tokenizer_test_2() {
+            let markup = String::from("<div data-source={\"root\"} render-if={12+2==14}>
+            <span data-source={\"hi\"}>hi</span>
+              <img
+              width={100} height={100} alt={2 + 2 == 4 ?   1 :0}/>
+              <span style={\"color:red;font-size:2rem;font-family:sans-serif;padding:3rem\"}>hello world</span>
+            </div> ");
+            let expected_arr: Vec<CurrentState> = vec![
+                CurrentState {
+                    state: TokenizerState::OpenAngleBracket,
+                    token: String::from("<"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::TagNameOpen,
+                    token: String::from("div"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::Props,
+                    token: String::from("data-source={\"root\"}"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::Props,
+                    token: String::from("render-if={12+2==14}"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::CloseAngleBracket,
+                    token: String::from(">"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::OpenAngleBracket,
+                    token: String::from("<"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::TagNameOpen,
+                    token: String::from("span"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::Props,
+                    token: String::from("data-source={\"hi\"}"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::CloseAngleBracket,
+                    token: String::from(">"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::Text,
+                    token: String::from("hi"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::ClosingAngleBracket,
+                    token: String::from("</"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::TagNameClose,
+                    token: String::from("span"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::CloseAngleBracket,
+                    token: String::from(">"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::OpenAngleBracket,
+                    token: String::from("<"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::TagNameOpen,
+                    token: String::from("img"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::Props,
+                    token: String::from("width={100}"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::Props,
+                    token: String::from("height={100}"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::Props,
+                    token: String::from("alt={2 + 2 == 4 ?   1 :0}"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::SelfClosingAngleBracket,
+                    token: String::from("/>"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::OpenAngleBracket,
+                    token: String::from("<"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::TagNameOpen,
+                    token: String::from("span"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::Props,
+                    token: String::from(
+                        "style={\"color:red;font-size:2rem;font-family:sans-serif;padding:3rem\"}",
+                    ),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::CloseAngleBracket,
+                    token: String::from(">"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::Text,
+                    token: String::from("hello world"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::ClosingAngleBracket,
+                    token: String::from("</"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::TagNameClose,
+                    token: String::from("span"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::CloseAngleBracket,
+                    token: String::from(">"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::ClosingAngleBracket,
+                    token: String::from("</"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::TagNameClose,
+                    token: String::from("div"),
+                    ..Default::default()
+                },
+                CurrentState {
+                    state: TokenizerState::CloseAngleBracket,
+                    token: String::from(">"),
+                    ..Default::default()
+                },
+            ];
+            let mut generator = tokenizer(markup);
+            for expected in expected_arr {
+                let actual_result = generator();
+                assert!(actual_result.is_ok());
+                let actual = actual_result.unwrap();
+                assert!(actual.state == expected.state && actual.token == expected.token);
+            }
+        }
+
+        #[test]
+        /// Spans of tokens must point to where they start in the markup, without the whitespace before
+        /// them; `line` and `column` start from 1.
+        fn test_tokenizer_spans() {
+            let markup = String::from("<div>\n  <p id={\"x\"}>hi</p>\n</div>");
+            let mut generator = tokenizer(markup);
+            let mut spans: Vec<(String, Span)> = Vec::new();
+            loop {
+                let CurrentState { state, token, span } = generator().unwrap();
+                if matches!(state, TokenizerState::Finalized) {
+                    break;
+                }
+                spans.push((token, span));
+            }
+            let get_span_of = |token: &str| spans.iter().find(|(t, _)| t == token).unwrap().1;
+            assert_eq!(
+                get_span_of("p"),
+                Span {
+                    start: 9,
+                    end: 10,
+                    line: 2,
+                    column: 4
+                }
+            );
+            assert_eq!(
+                get_span_of("id={\"x\"}"),
+                Span {
+                    start: 11,
+                    end: 19,
+                    line: 2,
+                    column: 6
+                }
+            );
+            assert_eq!(get_span_of("hi").column, 15);
+        }
+
+        #[test]
+        /// `<>` and `</>` must be read as the opening and closing tags of a fragment.
+        fn test_tokenizer_fragments() {
+            let markup = String::from("<><p>hi</p></ >");
+            let mut generator = tokenizer(markup);
+            let mut tokens: Vec<(TokenizerState, String)> = Vec::new();
+            loop {
+                let CurrentState { state, token, .. } = generator().unwrap();
+                if matches!(state, TokenizerState::Finalized) {
+                    break;
+                }
+                tokens.push((state, token));
+            }
+            assert_eq!(
+                tokens[..3],
+                [
+                    (TokenizerState::OpenAngleBracket, "<".to_owned()),
+                    (TokenizerState::TagNameOpen, FRAGMENT_TAG_NAME.to_owned()),
+                    (TokenizerState::CloseAngleBracket, ">".to_owned()),
+                ]
+            );
+            assert_eq!(
+                tokens[tokens.len() - 3..],
+                [
+                    (TokenizerState::ClosingAngleBracket, "</".to_owned()),
+                    (TokenizerState::TagNameClose, FRAGMENT_TAG_NAME.to_owned()),
+                    (TokenizerState::CloseAngleBracket, ">".to_owned()),
+                ]
+            );
+        }
+
+        #[test]
+        /// The content of raw text elements must be read as a single text, up to their closing tag,
+        /// whatever it contains; an empty one must not produce a text at all.
+        fn test_tokenizer_raw_text() {
+            let markup = String::from(
+                "<style media=\"all\">.a{color:red}\n</p><b></STYLE ><script></script><p>{x}</p>",
+            );
+            let mut generator = tokenizer(markup);
+            let mut tokens: Vec<(TokenizerState, String)> = Vec::new();
+            loop {
+                let CurrentState { state, token, .. } = generator().unwrap();
+                if matches!(state, TokenizerState::Finalized) {
+                    break;
+                }
+                tokens.push((state, token));
+            }
+            assert_eq!(
+                tokens[3..7],
+                [
+                    (TokenizerState::CloseAngleBracket, ">".to_owned()),
+                    (TokenizerState::Text, ".a{color:red}\n</p><b>".to_owned()),
+                    (TokenizerState::ClosingAngleBracket, "</".to_owned()),
+                    (TokenizerState::TagNameClose, "STYLE".to_owned()),
+                ]
+            );
+            assert_eq!(
+                tokens[9..13],
+                [
+                    (TokenizerState::TagNameOpen, "script".to_owned()),
+                    (TokenizerState::CloseAngleBracket, ">".to_owned()),
+                    (TokenizerState::ClosingAngleBracket, "</".to_owned()),
+                    (TokenizerState::TagNameClose, "script".to_owned()),
+                ]
+            );
+            assert_eq!(
+                tokens[tokens.len() - 4],
+                (TokenizerState::Text, "{x}".to_owned())
+            );
+        }
+
+        #[test]
+        /// A raw text element which is never closed must be an error.
+        fn test_tokenizer_raw_text_unclosed() {
+            let mut generator = tokenizer(String::from("<script>if (a < b) {}"));
+            let result = loop {
+                let next = generator();
+                if next.is_err()
+                    || matches!(
+                        next,
+                        Ok(CurrentState {
+                            state: TokenizerState::Finalized,
+                            ..
+                        })
+                    )
+                {
+                    break next;
+                }
+            };
+            assert!(matches!(result, Err(Error::ParsingError(_))));
+        }
+
+        #[test]
+        /// Comments may span multiple lines and contain `<`, `>` and `{`; they must be read as a single
+        /// `Comment` token, and text right before them must still be a separate token.
+        fn test_tokenizer_comments() {
+            let markup = String::from(
+                "<div>hi<!-- <b>{state.x}</b>\n  a < b --><p>{state.y}</p><!----></div>",
+            );
+            let mut generator = tokenizer(markup);
+            let mut tokens: Vec<(TokenizerState, String)> = Vec::new();
+            loop {
+                let CurrentState { state, token, .. } = generator().unwrap();
+                if matches!(state, TokenizerState::Finalized) {
+                    break;
+                }
+                tokens.push((state, token));
+            }
+            assert_eq!(
+                tokens[3..6],
+                [
+                    (TokenizerState::Text, "hi".to_owned()),
+                    (
+                        TokenizerState::Comment,
+                        " <b>{state.x}</b>\n  a < b ".to_owned()
+                    ),
+                    (TokenizerState::OpenAngleBracket, "<".to_owned()),
+                ]
+            );
+            assert!(tokens.contains(&(TokenizerState::Comment, "".to_owned())));
+            assert_eq!(tokens.last().unwrap().1, ">");
+        }
+
+        #[test]
+        /// A comment without `-->` must be reported, pointing at where it started.
+        fn test_tokenizer_unclosed_comment() {
+            let markup = String::from("<div>\n  <!-- <p>hi</p>\n</div>");
+            let mut generator = tokenizer(markup);
+            let error = loop {
+                let next_result = generator();
+                if next_result.is_err() {
+                    break next_result.unwrap_err();
+                }
+            };
+            assert!(
+                matches!(error, Error::ParsingError(msg) if msg.contains("never closed") && msg.contains("line 2, column 3"))
+            );
+        }
+
+        #[test]
+        /// Errors which the tokenizer returns must mention where in the markup they happened.
+        fn test_tokenizer_error_location() {
+            let markup = String::from("<div>\n  <p id=x>hi</p>\n</div>");
+            let mut generator = tokenizer(markup);
+            let error = loop {
+                let next_result = generator();
+                if next_result.is_err() {
+                    break next_result.unwrap_err();
+                }
+            };
+            assert!(
+                matches!(error, Error::ParsingError(msg) if msg.contains("line 2, column 6") && msg.contains("2 |   <p id=x>hi</p>"))
+            );
+        }
+    }
+}